@@ -0,0 +1,53 @@
+use skia_safe::{Color, Color4f, Point, Rect};
+
+/// A value that can be interpolated by a `Tween`.
+/// `t` can leave the `0..=1` range with overshooting easings such as springs.
+pub trait Animatable: Copy {
+    fn lerp(from: Self, to: Self, t: f32) -> Self;
+}
+
+impl Animatable for f32 {
+    fn lerp(from: Self, to: Self, t: f32) -> Self {
+        from + (to - from) * t
+    }
+}
+
+impl Animatable for Color {
+    fn lerp(from: Self, to: Self, t: f32) -> Self {
+        let channel = |from: u8, to: u8| f32::lerp(from as f32, to as f32, t).round().clamp(0.0, 255.0) as u8;
+        Color::from_argb(
+            channel(from.a(), to.a()),
+            channel(from.r(), to.r()),
+            channel(from.g(), to.g()),
+            channel(from.b(), to.b()),
+        )
+    }
+}
+
+impl Animatable for Color4f {
+    fn lerp(from: Self, to: Self, t: f32) -> Self {
+        Color4f {
+            r: f32::lerp(from.r, to.r, t),
+            g: f32::lerp(from.g, to.g, t),
+            b: f32::lerp(from.b, to.b, t),
+            a: f32::lerp(from.a, to.a, t).clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl Animatable for Point {
+    fn lerp(from: Self, to: Self, t: f32) -> Self {
+        Point::new(f32::lerp(from.x, to.x, t), f32::lerp(from.y, to.y, t))
+    }
+}
+
+impl Animatable for Rect {
+    fn lerp(from: Self, to: Self, t: f32) -> Self {
+        Rect::new(
+            f32::lerp(from.left, to.left, t),
+            f32::lerp(from.top, to.top, t),
+            f32::lerp(from.right, to.right, t),
+            f32::lerp(from.bottom, to.bottom, t),
+        )
+    }
+}
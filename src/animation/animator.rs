@@ -0,0 +1,234 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Instant;
+use winit::window::WindowId;
+use crate::animation::animatable::Animatable;
+use crate::animation::tween::Tween;
+use crate::application::TimerId;
+use crate::context::window_context::WindowContext;
+
+pub(crate) type EndCallback = Box<dyn FnOnce(&mut WindowContext)>;
+
+struct AnimationState<T: Animatable> {
+    tween: Tween<T>,
+    start: Instant,
+    value: T,
+    finished: bool,
+    cancelled: bool,
+    on_end: Option<EndCallback>,
+}
+
+/// Handle to a running tween, returned by `WindowContext::animate`.
+/// The value is advanced right before every frame of the window until the tween finishes.
+pub struct Animation<T: Animatable> {
+    id: TimerId,
+    state: Rc<RefCell<AnimationState<T>>>,
+}
+
+impl<T: Animatable> Animation<T> {
+    pub(crate) fn new(id: TimerId, tween: Tween<T>, start: Instant) -> Self {
+        let (value, finished) = tween.sample(Default::default());
+        Self {
+            id,
+            state: Rc::new(RefCell::new(AnimationState {
+                tween,
+                start,
+                value,
+                finished,
+                cancelled: false,
+                on_end: None,
+            })),
+        }
+    }
+
+    pub fn id(&self) -> TimerId {
+        self.id
+    }
+
+    pub fn value(&self) -> T {
+        self.state.borrow().value
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.borrow().finished
+    }
+
+    /// Runs `callback` once the animation completes, right after `UIEvent::AnimationEnd` is delivered.
+    /// Replaces any callback set before.
+    pub fn on_end(&self, callback: impl FnOnce(&mut WindowContext) + 'static) {
+        self.state.borrow_mut().on_end = Some(Box::new(callback));
+    }
+
+    /// Stops the animation at its current value without sending `UIEvent::AnimationEnd` or running `on_end`.
+    pub fn cancel(&self) {
+        let mut state = self.state.borrow_mut();
+        state.cancelled = true;
+        state.on_end = None;
+    }
+}
+
+impl<T: Animatable> Clone for Animation<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            state: self.state.clone(),
+        }
+    }
+}
+
+pub(crate) enum AnimationStatus {
+    Running,
+    Finished(Option<EndCallback>),
+    Cancelled,
+}
+
+/// An animation that completed during `Animator::tick`.
+pub(crate) struct FinishedAnimation {
+    pub id: TimerId,
+    pub on_end: Option<EndCallback>,
+}
+
+pub(crate) trait AnimationTick {
+    fn id(&self) -> TimerId;
+
+    fn tick(&self, now: Instant) -> AnimationStatus;
+}
+
+impl<T: Animatable> AnimationTick for Animation<T> {
+    fn id(&self) -> TimerId {
+        self.id
+    }
+
+    fn tick(&self, now: Instant) -> AnimationStatus {
+        let mut state = self.state.borrow_mut();
+        if state.cancelled {
+            return AnimationStatus::Cancelled;
+        }
+        let (value, finished) = state.tween.sample(now.saturating_duration_since(state.start));
+        state.value = value;
+        state.finished = finished;
+        if finished {
+            AnimationStatus::Finished(state.on_end.take())
+        } else {
+            AnimationStatus::Running
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Animator {
+    animation_map: HashMap<WindowId, Vec<Box<dyn AnimationTick>>>,
+}
+
+impl Animator {
+    pub fn add(&mut self, window_id: WindowId, animation: Box<dyn AnimationTick>) {
+        self.animation_map.entry(window_id).or_default().push(animation);
+    }
+
+    pub fn remove_window(&mut self, window_id: WindowId) {
+        self.animation_map.remove(&window_id);
+    }
+
+    pub fn window_ids(&self) -> impl Iterator<Item = WindowId> + '_ {
+        self.animation_map.keys().copied()
+    }
+
+    /// Advances every animation of the window, returning the finished ones and whether any is still running.
+    pub fn tick(&mut self, window_id: WindowId, now: Instant) -> (Vec<FinishedAnimation>, bool) {
+        let Some(animations) = self.animation_map.get_mut(&window_id) else {
+            return (vec![], false);
+        };
+        let mut finished = vec![];
+        animations.retain(|animation| {
+            match animation.tick(now) {
+                AnimationStatus::Running => true,
+                AnimationStatus::Finished(on_end) => {
+                    finished.push(FinishedAnimation { id: animation.id(), on_end });
+                    false
+                }
+                AnimationStatus::Cancelled => false,
+            }
+        });
+        let running = !animations.is_empty();
+        if !running {
+            self.animation_map.remove(&window_id);
+        }
+        (finished, running)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::*;
+    use crate::clock::{Clock, VirtualClock};
+
+    fn window_id() -> WindowId {
+        unsafe { WindowId::dummy() }
+    }
+
+    fn tick(animator: &mut Animator, now: Instant) -> (Vec<TimerId>, bool) {
+        let (finished, running) = animator.tick(window_id(), now);
+        (finished.into_iter().map(|animation| animation.id).collect(), running)
+    }
+
+    #[test]
+    fn advances_with_the_clock() {
+        let clock = VirtualClock::new();
+        let mut animator = Animator::default();
+        let animation = Animation::new(TimerId::default(), Tween::new(0.0f32, 100.0, Duration::from_millis(100)), clock.now());
+        animator.add(window_id(), Box::new(animation.clone()));
+
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(tick(&mut animator, clock.now()), (vec![], true));
+        assert!((animation.value() - 50.0).abs() < 1e-3);
+
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(tick(&mut animator, clock.now()), (vec![TimerId::default()], false));
+        assert_eq!(animation.value(), 100.0);
+        assert!(animation.is_finished());
+    }
+
+    #[test]
+    fn cancelled_animation_keeps_its_value() {
+        let clock = VirtualClock::new();
+        let mut animator = Animator::default();
+        let animation = Animation::new(TimerId::default(), Tween::new(0.0f32, 100.0, Duration::from_millis(100)), clock.now());
+        animator.add(window_id(), Box::new(animation.clone()));
+        clock.advance(Duration::from_millis(25));
+        tick(&mut animator, clock.now());
+        animation.cancel();
+        clock.advance(Duration::from_millis(75));
+        assert_eq!(tick(&mut animator, clock.now()), (vec![], false));
+        assert!((animation.value() - 25.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn end_callback_is_handed_over_once_finished() {
+        let clock = VirtualClock::new();
+        let mut animator = Animator::default();
+        let animation = Animation::new(TimerId::default(), Tween::new(0.0f32, 1.0, Duration::from_millis(10)), clock.now());
+        animation.on_end(|_| {});
+        animator.add(window_id(), Box::new(animation.clone()));
+        let (finished, _) = animator.tick(window_id(), clock.now());
+        assert!(finished.is_empty());
+        clock.advance(Duration::from_millis(10));
+        let (finished, _) = animator.tick(window_id(), clock.now());
+        assert!(finished[0].on_end.is_some());
+    }
+
+    #[test]
+    fn cancel_drops_end_callback() {
+        let clock = VirtualClock::new();
+        let mut animator = Animator::default();
+        let animation = Animation::new(TimerId::default(), Tween::new(0.0f32, 1.0, Duration::from_millis(10)), clock.now());
+        animation.on_end(|_| {});
+        animator.add(window_id(), Box::new(animation.clone()));
+        animation.cancel();
+        clock.advance(Duration::from_millis(10));
+        let (finished, running) = animator.tick(window_id(), clock.now());
+        assert!(finished.is_empty());
+        assert!(!running);
+    }
+}
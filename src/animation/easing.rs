@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+/// How close to the end value a spring has to stay to be at rest, relative to the distance it travels.
+const SPRING_REST: f32 = 1e-3;
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    Ease,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Control points `(x1, y1, x2, y2)` of a CSS-style cubic bezier curve.
+    CubicBezier(f32, f32, f32, f32),
+    /// Damped spring released from the start value towards the end value, sampled in real time.
+    /// A tween with a spring lasts until the spring is at rest, whatever its duration. Springs without damping
+    /// never come to rest, their tween is cut off at the end of its duration.
+    Spring { stiffness: f32, damping: f32, mass: f32 },
+}
+
+impl Easing {
+    /// How long a tween of `duration` with this easing lasts.
+    pub fn duration(&self, duration: Duration) -> Duration {
+        match *self {
+            Easing::Spring { stiffness, damping, mass } => spring_rest_time(stiffness, damping, mass)
+                .map(Duration::from_secs_f32)
+                .unwrap_or(duration),
+            _ => duration,
+        }
+    }
+
+    pub fn ease(&self, progress: f32, duration: Duration) -> f32 {
+        if progress <= 0.0 {
+            return 0.0;
+        }
+        if progress >= 1.0 {
+            return 1.0;
+        }
+        match *self {
+            Easing::Linear => progress,
+            Easing::Ease => cubic_bezier(0.25, 0.1, 0.25, 1.0, progress),
+            Easing::EaseIn => cubic_bezier(0.42, 0.0, 1.0, 1.0, progress),
+            Easing::EaseOut => cubic_bezier(0.0, 0.0, 0.58, 1.0, progress),
+            Easing::EaseInOut => cubic_bezier(0.42, 0.0, 0.58, 1.0, progress),
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, progress),
+            Easing::Spring { stiffness, damping, mass } => {
+                spring(stiffness, damping, mass, progress * duration.as_secs_f32())
+            }
+        }
+    }
+}
+
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    let sample = |a1: f32, a2: f32, t: f32| {
+        let u = 1.0 - t;
+        3.0 * a1 * u * u * t + 3.0 * a2 * u * t * t + t * t * t
+    };
+    let slope = |a1: f32, a2: f32, t: f32| {
+        let u = 1.0 - t;
+        3.0 * a1 * u * u + 6.0 * (a2 - a1) * u * t + 3.0 * (1.0 - a2) * t * t
+    };
+
+    // Newton's method converges quickly for most curves, fall back to bisection when the slope is flat.
+    let mut t = x;
+    for _ in 0..8 {
+        let error = sample(x1, x2, t) - x;
+        if error.abs() < 1e-6 {
+            return sample(y1, y2, t);
+        }
+        let d = slope(x1, x2, t);
+        if d.abs() < 1e-6 {
+            break;
+        }
+        t -= error / d;
+    }
+
+    let (mut low, mut high) = (0.0, 1.0);
+    t = x;
+    for _ in 0..32 {
+        let value = sample(x1, x2, t);
+        if (value - x).abs() < 1e-6 {
+            break;
+        }
+        if value < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.0;
+    }
+    sample(y1, y2, t)
+}
+
+fn spring(stiffness: f32, damping: f32, mass: f32, t: f32) -> f32 {
+    let omega = (stiffness / mass).sqrt();
+    let zeta = damping / (2.0 * (stiffness * mass).sqrt());
+    if zeta < 1.0 {
+        let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+        let envelope = (-zeta * omega * t).exp();
+        1.0 - envelope * ((omega_d * t).cos() + (zeta * omega / omega_d) * (omega_d * t).sin())
+    } else if zeta == 1.0 {
+        1.0 - (-omega * t).exp() * (1.0 + omega * t)
+    } else {
+        let root = (zeta * zeta - 1.0).sqrt();
+        let r1 = -omega * (zeta - root);
+        let r2 = -omega * (zeta + root);
+        1.0 - (r2 * (r1 * t).exp() - r1 * (r2 * t).exp()) / (r2 - r1)
+    }
+}
+
+/// Seconds after which the spring stays within `SPRING_REST` of its end, from the envelope of its motion.
+/// `None` if it never comes to rest.
+fn spring_rest_time(stiffness: f32, damping: f32, mass: f32) -> Option<f32> {
+    if stiffness <= 0.0 || damping <= 0.0 || mass <= 0.0 {
+        return None;
+    }
+    let omega = (stiffness / mass).sqrt();
+    let zeta = damping / (2.0 * (stiffness * mass).sqrt());
+    let time = if zeta < 1.0 {
+        let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+        let amplitude = (1.0 + (zeta * omega / omega_d).powi(2)).sqrt();
+        (amplitude / SPRING_REST).ln() / (zeta * omega)
+    } else if zeta == 1.0 {
+        // Solves (1 + ωt)e^(-ωt) = SPRING_REST, the iteration converges in a few steps.
+        let mut time = (1.0 / SPRING_REST).ln() / omega;
+        for _ in 0..8 {
+            time = ((1.0 + omega * time) / SPRING_REST).ln() / omega;
+        }
+        time
+    } else {
+        let root = (zeta * zeta - 1.0).sqrt();
+        let slow = omega * (zeta - root);
+        let fast = omega * (zeta + root);
+        let amplitude = (slow + fast) / (fast - slow);
+        (amplitude / SPRING_REST).ln() / slow
+    };
+    Some(time.max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn linear_bezier_is_identity() {
+        for x in [0.1, 0.25, 0.5, 0.9] {
+            assert!(close(cubic_bezier(0.0, 0.0, 1.0, 1.0, x), x));
+        }
+    }
+
+    #[test]
+    fn ease_in_out_is_symmetric() {
+        for x in [0.1, 0.3, 0.45] {
+            let a = cubic_bezier(0.42, 0.0, 0.58, 1.0, x);
+            let b = cubic_bezier(0.42, 0.0, 0.58, 1.0, 1.0 - x);
+            assert!(close(a + b, 1.0));
+        }
+        assert!(close(cubic_bezier(0.42, 0.0, 0.58, 1.0, 0.5), 0.5));
+    }
+
+    #[test]
+    fn ease_in_starts_slow_and_ease_out_fast() {
+        assert!(Easing::EaseIn.ease(0.25, Duration::ZERO) < 0.25);
+        assert!(Easing::EaseOut.ease(0.25, Duration::ZERO) > 0.25);
+    }
+
+    #[test]
+    fn bezier_converges_on_flat_slopes() {
+        // x(t) is flat at t = 0.5, where Newton's method can't make progress.
+        let y = cubic_bezier(1.0, 0.0, 0.0, 1.0, 0.5);
+        assert!(close(y, 0.5));
+    }
+
+    #[test]
+    fn ends_are_exact() {
+        for easing in [Easing::Ease, Easing::CubicBezier(0.3, 1.5, 0.7, -0.5), Easing::Spring { stiffness: 100.0, damping: 10.0, mass: 1.0 }] {
+            assert_eq!(easing.ease(0.0, Duration::from_secs(1)), 0.0);
+            assert_eq!(easing.ease(1.0, Duration::from_secs(1)), 1.0);
+        }
+    }
+
+    #[test]
+    fn springs_settle_on_the_target() {
+        for damping in [5.0, 20.0, 40.0] {
+            let value = spring(100.0, damping, 1.0, 5.0);
+            assert!(close(value, 1.0), "damping {} settled at {}", damping, value);
+        }
+    }
+
+    #[test]
+    fn springs_are_at_rest_after_their_rest_time() {
+        for damping in [2.0, 10.0, 20.0, 60.0] {
+            let rest = spring_rest_time(100.0, damping, 1.0).unwrap();
+            for step in 0..100 {
+                let value = spring(100.0, damping, 1.0, rest + step as f32 * 0.01);
+                assert!((value - 1.0).abs() <= SPRING_REST, "damping {} at {}s is {}", damping, rest, value);
+            }
+        }
+    }
+
+    #[test]
+    fn spring_duration_is_its_rest_time() {
+        let easing = Easing::Spring { stiffness: 100.0, damping: 2.0, mass: 1.0 };
+        let duration = easing.duration(Duration::from_millis(100));
+        assert!(duration > Duration::from_secs(1));
+        // Nothing left to jump over once the tween ends.
+        let before_end = easing.ease(0.999, duration);
+        assert!((before_end - 1.0).abs() <= SPRING_REST);
+    }
+
+    #[test]
+    fn undamped_spring_keeps_the_tween_duration() {
+        let easing = Easing::Spring { stiffness: 100.0, damping: 0.0, mass: 1.0 };
+        assert_eq!(easing.duration(Duration::from_millis(300)), Duration::from_millis(300));
+        assert_eq!(Easing::EaseIn.duration(Duration::from_millis(300)), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn underdamped_spring_overshoots() {
+        let peak = (1..100).map(|step| spring(100.0, 5.0, 1.0, step as f32 / 100.0)).fold(0.0, f32::max);
+        assert!(peak > 1.0);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod easing;
+pub mod animatable;
+pub mod tween;
+pub mod animator;
\ No newline at end of file
@@ -0,0 +1,171 @@
+use std::time::Duration;
+use crate::animation::animatable::Animatable;
+use crate::animation::easing::Easing;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Repeat {
+    /// Play the tween this many times in total.
+    Count(u32),
+    Forever,
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Repeat::Count(1)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Tween<T: Animatable> {
+    from: T,
+    to: T,
+    duration: Duration,
+    delay: Duration,
+    repeat: Repeat,
+    alternate: bool,
+    easing: Easing,
+}
+
+impl<T: Animatable> Tween<T> {
+    pub fn new(from: T, to: T, duration: Duration) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            delay: Duration::ZERO,
+            repeat: Repeat::default(),
+            alternate: false,
+            easing: Easing::default(),
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Plays every other repetition backwards.
+    pub fn with_alternate(mut self, alternate: bool) -> Self {
+        self.alternate = alternate;
+        self
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Returns the value after `elapsed` and whether the tween has finished.
+    pub fn sample(&self, elapsed: Duration) -> (T, bool) {
+        if elapsed < self.delay {
+            return (self.from, false);
+        }
+        let elapsed = elapsed - self.delay;
+        let duration = self.easing.duration(self.duration);
+        let (iteration, mut progress) = if duration.is_zero() {
+            (u32::MAX, 1.0)
+        } else {
+            // Integer math keeps exact multiples of the duration from landing just short of the next iteration.
+            let duration = duration.as_nanos();
+            let iteration = (elapsed.as_nanos() / duration).min(u32::MAX as u128) as u32;
+            let progress = (elapsed.as_nanos() % duration) as f64 / duration as f64;
+            (iteration, progress as f32)
+        };
+        let (iteration, finished) = match self.repeat {
+            Repeat::Count(count) if iteration >= count.max(1) => {
+                progress = 1.0;
+                (count.max(1) - 1, true)
+            }
+            _ => (iteration, false),
+        };
+        if self.alternate && iteration % 2 == 1 {
+            progress = 1.0 - progress;
+        }
+        let t = self.easing.ease(progress, duration);
+        (T::lerp(self.from, self.to, t), finished)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn samples_between_the_ends() {
+        let tween = Tween::new(10.0f32, 20.0, ms(100));
+        assert_eq!(tween.sample(ms(0)), (10.0, false));
+        let (value, finished) = tween.sample(ms(25));
+        assert!(close(value, 12.5) && !finished);
+        assert_eq!(tween.sample(ms(100)), (20.0, true));
+        assert_eq!(tween.sample(ms(500)), (20.0, true));
+    }
+
+    #[test]
+    fn holds_the_start_during_the_delay() {
+        let tween = Tween::new(0.0f32, 1.0, ms(100)).with_delay(ms(50));
+        assert_eq!(tween.sample(ms(40)), (0.0, false));
+        let (value, _) = tween.sample(ms(100));
+        assert!(close(value, 0.5));
+        assert_eq!(tween.sample(ms(150)), (1.0, true));
+    }
+
+    #[test]
+    fn repeats_the_given_number_of_times() {
+        let tween = Tween::new(0.0f32, 1.0, ms(100)).with_repeat(Repeat::Count(3));
+        let (value, finished) = tween.sample(ms(250));
+        assert!(close(value, 0.5) && !finished);
+        assert_eq!(tween.sample(ms(300)), (1.0, true));
+    }
+
+    #[test]
+    fn alternate_plays_odd_repetitions_backwards() {
+        let tween = Tween::new(0.0f32, 1.0, ms(100)).with_repeat(Repeat::Count(2)).with_alternate(true);
+        let (value, _) = tween.sample(ms(125));
+        assert!(close(value, 0.75));
+        // Ends where the last, backwards repetition ends.
+        assert_eq!(tween.sample(ms(200)), (0.0, true));
+    }
+
+    #[test]
+    fn forever_never_finishes() {
+        let tween = Tween::new(0.0f32, 1.0, ms(100)).with_repeat(Repeat::Forever).with_alternate(true);
+        let (value, finished) = tween.sample(Duration::from_secs(3600) + ms(150));
+        assert!(close(value, 0.5) && !finished);
+    }
+
+    #[test]
+    fn zero_duration_jumps_to_the_end() {
+        let tween = Tween::new(0.0f32, 1.0, Duration::ZERO);
+        assert_eq!(tween.sample(Duration::ZERO), (1.0, true));
+    }
+
+    #[test]
+    fn spring_runs_until_at_rest() {
+        let easing = Easing::Spring { stiffness: 100.0, damping: 4.0, mass: 1.0 };
+        let tween = Tween::new(0.0f32, 1.0, ms(100)).with_easing(easing);
+        let (_, finished) = tween.sample(ms(200));
+        assert!(!finished);
+        let (value, finished) = tween.sample(easing.duration(ms(100)));
+        assert!(finished && value == 1.0);
+    }
+
+    #[test]
+    fn easing_shapes_the_progress() {
+        let tween = Tween::new(0.0f32, 1.0, ms(100)).with_easing(Easing::EaseIn);
+        let (value, _) = tween.sample(ms(50));
+        assert!(value < 0.5);
+    }
+}
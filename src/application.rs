@@ -1,12 +1,41 @@
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant};
-use winit::event::{Event, StartCause};
+use winit::event::{Event, StartCause, TouchPhase, WindowEvent};
+use winit::keyboard::{Key, NamedKey, PhysicalKey};
 use winit::event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopProxy, EventLoopWindowTarget};
-use winit::window::WindowId;
+use winit::window::{WindowBuilder, WindowId};
+use crate::animation::animatable::Animatable;
+use crate::animation::animator::{Animation, Animator, FinishedAnimation};
+use crate::animation::tween::Tween;
+use crate::clipboard::Clipboard;
+use crate::clock::{Clock, SystemClock};
 use crate::context::context::Context;
+use crate::context::window_context::FrameCallback;
 use crate::custom_event::CustomEvent;
+use crate::event::input;
+use crate::event::input::{Modifiers, PointerId};
+use crate::event::input_event::InputEvent;
+use crate::event::recording::{InputRecording, RecordedInput, Replay, ReplaySpeed};
+use crate::event::synthetic::{Delivery, SyntheticEvent};
+use crate::event::window_message::WindowMessage;
 use crate::event_handler::EventHandler;
+use crate::lifecycle::Lifecycle;
+#[cfg(feature = "gamepad")]
+use crate::gamepad::{Gamepad, GamepadBackend, GamepadRouter, GamepadWaker};
+use crate::shortcut::{CommandRegistry, ShortcutConflict, ShortcutMatch};
+use crate::scheduler::Scheduler;
+use crate::single_instance;
+use crate::single_instance::{Acquired, InstanceArgs, SingleInstanceError};
 use crate::window::Window;
+use crate::window_control::WindowControl;
+use crate::window_state::{WindowGeometry, WindowStateManager};
+use crate::window_tree::WindowTree;
+
+const WINDOW_STATE_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+pub type TimerCallback = Box<dyn FnOnce(TimerId, &mut Application, &EventLoopWindowTarget<CustomEvent>)>;
+pub type IntervalCallback = Box<dyn Fn(TimerId, &mut Application, &EventLoopWindowTarget<CustomEvent>)>;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct TimerId(u32);
@@ -19,69 +48,536 @@ impl TimerId {
 
 pub struct Application {
     event_loop_proxy: EventLoopProxy<CustomEvent>,
+    clock: Box<dyn Clock>,
     window_map: HashMap<WindowId, Window>,
-    timer_map: HashMap<TimerId, (Instant, Box<dyn FnOnce(TimerId, &mut Application, &EventLoopWindowTarget<CustomEvent>)>)>,
-    timer_id: TimerId,
-    interval_map: HashMap<TimerId, (Instant, Duration, Box<dyn Fn(TimerId, &mut Application, &EventLoopWindowTarget<CustomEvent>)>)>,
-    interval_id: TimerId,
+    timers: Scheduler<TimerCallback>,
+    intervals: Scheduler<IntervalCallback>,
+    animation_frame_map: HashMap<WindowId, Vec<(TimerId, FrameCallback)>>,
+    animation_frame_id: TimerId,
+    animator: Animator,
+    animation_id: TimerId,
+    window_tree: WindowTree,
+    popup_focus_lost: bool,
+    lifecycle: Lifecycle,
+    window_name_map: HashMap<String, WindowId>,
+    message_queue: VecDeque<(Option<WindowId>, WindowMessage)>,
+    window_state_manager: Option<WindowStateManager>,
+    window_state_saved_at: Instant,
+    focused_window: Option<WindowId>,
+    focus_history: Vec<WindowId>,
+    fullscreen_restore_map: HashMap<WindowId, WindowGeometry>,
+    pointer_capture_map: HashMap<WindowId, Vec<PointerId>>,
+    commands: CommandRegistry,
+    window_commands_map: HashMap<WindowId, CommandRegistry>,
+    clipboard: Clipboard,
+    recording_map: HashMap<WindowId, InputRecording>,
+    replay_map: HashMap<WindowId, Replay>,
+    /// Set while replayed input is delivered, which isn't recorded again.
+    replaying: bool,
+    synthetic_queue: VecDeque<(WindowId, SyntheticEvent)>,
+    #[cfg(feature = "gamepad")]
+    gamepads: GamepadRouter,
 }
 
 impl Application {
     pub fn new(event_loop_proxy: EventLoopProxy<CustomEvent>) -> Self {
+        let clock = SystemClock;
         Self {
             event_loop_proxy,
+            clock: Box::new(clock),
             window_map: HashMap::new(),
-            timer_map: HashMap::new(),
-            timer_id: TimerId::default(),
-            interval_map: HashMap::new(),
-            interval_id: TimerId::default(),
+            timers: Scheduler::new(),
+            intervals: Scheduler::new(),
+            animation_frame_map: HashMap::new(),
+            animation_frame_id: TimerId::default(),
+            animator: Animator::default(),
+            animation_id: TimerId::default(),
+            window_tree: WindowTree::default(),
+            popup_focus_lost: false,
+            lifecycle: Lifecycle::default(),
+            window_name_map: HashMap::new(),
+            message_queue: VecDeque::new(),
+            window_state_manager: None,
+            window_state_saved_at: clock.now(),
+            focused_window: None,
+            focus_history: Vec::new(),
+            fullscreen_restore_map: HashMap::new(),
+            pointer_capture_map: HashMap::new(),
+            commands: CommandRegistry::new(),
+            window_commands_map: HashMap::new(),
+            clipboard: Clipboard::new(),
+            recording_map: HashMap::new(),
+            replay_map: HashMap::new(),
+            replaying: false,
+            synthetic_queue: VecDeque::new(),
+            #[cfg(feature = "gamepad")]
+            gamepads: GamepadRouter::default(),
         }
     }
 
-    pub fn add_timer(&mut self, time: Duration, f: Box<dyn FnOnce(TimerId, &mut Application, &EventLoopWindowTarget<CustomEvent>)>) -> TimerId {
-        let id = self.timer_id.next();
-        let now = Instant::now();
-        self.timer_map.insert(id, (now + time, f));
-        id
+    /// Replaces the time source, e.g. with a `VirtualClock` in tests.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+        self.window_state_saved_at = self.clock.now();
+    }
+
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    pub fn add_timer(&mut self, time: Duration, f: TimerCallback) -> TimerId {
+        let now = self.now();
+        self.timers.add_timeout(now, time, f)
     }
 
     pub fn remove_timer(&mut self, timer_id: TimerId) {
-        self.timer_map.remove(&timer_id);
+        self.timers.remove(timer_id);
     }
 
     pub fn check_and_run_timer(&mut self, event_loop: &EventLoopWindowTarget<CustomEvent>) {
-        if self.timer_map.is_empty() {
-            return;
-        }
-        let now = Instant::now();
-        let ids: Vec<_> = self.timer_map.iter().filter(|(_, data)| now >= data.0).map(|(id, _)| id.clone()).collect();
-        for id in ids {
-            let (_, f) = self.timer_map.remove(&id).unwrap();
-            f(id, self, event_loop);
+        let now = self.now();
+        for due in self.timers.take_due(now) {
+            (due.value)(due.id, self, event_loop);
         }
     }
 
-    pub fn add_interval(&mut self, time: Duration, f: Box<dyn Fn(TimerId, &mut Application, &EventLoopWindowTarget<CustomEvent>)>) -> TimerId {
-        let id = self.interval_id.next();
-        let now = Instant::now();
-        self.interval_map.insert(id, (now, time, f));
-        id
+    pub fn add_interval(&mut self, time: Duration, f: IntervalCallback) -> TimerId {
+        let now = self.now();
+        self.intervals.add_interval(now, time, f)
     }
 
     pub fn remove_interval(&mut self, timer_id: TimerId) {
-        self.timer_map.remove(&timer_id);
+        self.intervals.remove(timer_id);
     }
 
     pub fn check_and_run_interval(&mut self, event_loop: &EventLoopWindowTarget<CustomEvent>) {
-        if self.interval_map.is_empty() {
+        let now = self.now();
+        for due in self.intervals.take_due(now) {
+            (due.value)(due.id, self, event_loop);
+            self.intervals.reschedule(due, now);
+        }
+    }
+
+    /// How long the event loop may sleep: until the next timer, interval, replayed event or window
+    /// state save, or until the system sends an event when nothing is scheduled. A virtual clock doesn't
+    /// move while the loop sleeps, so its deadlines don't wake the loop.
+    pub fn control_flow(&self) -> ControlFlow {
+        if !self.message_queue.is_empty() || !self.synthetic_queue.is_empty() {
+            return ControlFlow::Poll;
+        }
+        let now = self.now();
+        let replays = self.replay_map.iter().filter_map(|(window_id, replay)| {
+            let window = self.window_map.get(window_id)?;
+            replay.next_due(window.created_at(), now)
+        });
+        let window_state_save = self.window_state_manager.as_ref().map(|_| self.window_state_saved_at + WINDOW_STATE_SAVE_INTERVAL);
+        let deadline = [self.timers.next_deadline(), self.intervals.next_deadline(), window_state_save]
+            .into_iter()
+            .flatten()
+            .chain(replays)
+            .min();
+        match deadline {
+            Some(deadline) if deadline > now && self.clock.is_virtual() => ControlFlow::Wait,
+            Some(deadline) if deadline > now => ControlFlow::WaitUntil(Instant::now() + (deadline - now)),
+            Some(_) => ControlFlow::Poll,
+            None => ControlFlow::Wait,
+        }
+    }
+
+    /// Runs everything due at the clock's time: timers, intervals, messages, replayed and injected
+    /// events, then the animations and animation frames of every window, without waiting for a redraw.
+    /// Meant for tests, after advancing a `VirtualClock`.
+    pub fn tick(&mut self, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        self.check_and_run_timer(event_loop);
+        self.check_and_run_interval(event_loop);
+        self.check_and_run_message(event_loop);
+        self.check_and_run_replay(event_loop);
+        self.check_and_run_synthetic_events(event_loop);
+        let mut window_ids: Vec<WindowId> = self.animation_frame_map.keys().copied().collect();
+        window_ids.extend(self.animator.window_ids().filter(|window_id| !self.animation_frame_map.contains_key(window_id)));
+        for window_id in window_ids {
+            if let Some(mut window) = self.remove_window(window_id) {
+                window.on_animation_frame(self, event_loop);
+                self.add_window(window_id, window);
+            }
+        }
+    }
+
+    pub fn add_animation_frame(&mut self, window_id: WindowId, callback: FrameCallback) -> TimerId {
+        self.animation_frame_id = self.animation_frame_id.next();
+        let id = self.animation_frame_id;
+        self.animation_frame_map.entry(window_id).or_default().push((id, callback));
+        id
+    }
+
+    pub fn remove_animation_frame(&mut self, window_id: WindowId, frame_id: TimerId) {
+        if let Some(frames) = self.animation_frame_map.get_mut(&window_id) {
+            frames.retain(|(id, _)| *id != frame_id);
+        }
+    }
+
+    pub fn take_animation_frames(&mut self, window_id: WindowId) -> Vec<(TimerId, FrameCallback)> {
+        self.animation_frame_map.remove(&window_id).unwrap_or_default()
+    }
+
+    pub fn add_animation<T: Animatable + 'static>(&mut self, window_id: WindowId, tween: Tween<T>) -> Animation<T> {
+        self.animation_id = self.animation_id.next();
+        let now = self.now();
+        let animation = Animation::new(self.animation_id, tween, now);
+        self.animator.add(window_id, Box::new(animation.clone()));
+        animation
+    }
+
+    /// Advances the animations of the window, returning the finished ones and whether any is still running.
+    pub(crate) fn tick_animations(&mut self, window_id: WindowId, now: Instant) -> (Vec<FinishedAnimation>, bool) {
+        self.animator.tick(window_id, now)
+    }
+
+    /// Assigns a name the window can be found and messaged by, replacing any window that had it before.
+    pub fn set_window_name(&mut self, window_id: WindowId, name: impl Into<String>) {
+        self.window_name_map.retain(|_, id| *id != window_id);
+        self.window_name_map.insert(name.into(), window_id);
+    }
+
+    pub fn find_window(&self, name: &str) -> Option<WindowId> {
+        self.window_name_map.get(name).copied()
+    }
+
+    pub fn window_name(&self, window_id: WindowId) -> Option<&str> {
+        self.window_name_map.iter().find(|(_, id)| **id == window_id).map(|(name, _)| name.as_str())
+    }
+
+    /// Enables saving and restoring the geometry of named windows.
+    pub fn set_window_state_manager(&mut self, manager: WindowStateManager) {
+        self.window_state_manager = Some(manager);
+    }
+
+    pub fn window_state_manager(&mut self) -> Option<&mut WindowStateManager> {
+        self.window_state_manager.as_mut()
+    }
+
+    pub fn restore_window_state(&self, name: &str, wb: WindowBuilder, event_loop: &EventLoopWindowTarget<CustomEvent>) -> WindowBuilder {
+        match &self.window_state_manager {
+            Some(manager) => manager.restore(name, wb, event_loop),
+            None => wb,
+        }
+    }
+
+    pub(crate) fn save_fullscreen_geometry(&mut self, window_id: WindowId, geometry: WindowGeometry) {
+        self.fullscreen_restore_map.insert(window_id, geometry);
+    }
+
+    pub(crate) fn take_fullscreen_geometry(&mut self, window_id: WindowId) -> Option<WindowGeometry> {
+        self.fullscreen_restore_map.remove(&window_id)
+    }
+
+    pub(crate) fn set_pointer_capture(&mut self, window_id: WindowId, pointer_id: PointerId) {
+        let captures = self.pointer_capture_map.entry(window_id).or_default();
+        if !captures.contains(&pointer_id) {
+            captures.push(pointer_id);
+        }
+    }
+
+    pub(crate) fn release_pointer_capture(&mut self, window_id: WindowId, pointer_id: PointerId) {
+        if let Some(captures) = self.pointer_capture_map.get_mut(&window_id) {
+            captures.retain(|id| *id != pointer_id);
+            if captures.is_empty() {
+                self.pointer_capture_map.remove(&window_id);
+            }
+        }
+    }
+
+    pub fn commands(&mut self) -> &mut CommandRegistry {
+        &mut self.commands
+    }
+
+    pub fn window_commands(&mut self, window_id: WindowId) -> &mut CommandRegistry {
+        self.window_commands_map.entry(window_id).or_default()
+    }
+
+    /// Conflicts among the application's commands, among the window's, and between both levels, where
+    /// the window's binding shadows the application's.
+    pub fn shortcut_conflicts(&self, window_id: WindowId) -> Vec<ShortcutConflict> {
+        let mut conflicts = self.commands.conflicts();
+        if let Some(commands) = self.window_commands_map.get(&window_id) {
+            conflicts.extend(commands.conflicts());
+            conflicts.extend(commands.conflicts_with(&self.commands));
+        }
+        conflicts
+    }
+
+    pub fn clipboard(&mut self) -> &mut Clipboard {
+        &mut self.clipboard
+    }
+
+    /// Bindings of the window come first, then the ones of the application.
+    pub(crate) fn lookup_shortcut(&self, window_id: WindowId, sequence: &[(Key, PhysicalKey, Modifiers)]) -> ShortcutMatch {
+        if let Some(commands) = self.window_commands_map.get(&window_id) {
+            match commands.lookup(sequence) {
+                ShortcutMatch::None => {}
+                found => return found,
+            }
+        }
+        self.commands.lookup(sequence)
+    }
+
+    /// Hands pointer input that reached `window_id` to another window holding the capture of the pointer,
+    /// with its position moved to that window's client area. Returns `true` if the input was redirected.
+    /// Entering and leaving stay with the window under the pointer.
+    fn redirect_captured_input(&mut self, window_id: WindowId, input: &InputEvent, event_loop: &EventLoopWindowTarget<CustomEvent>) -> bool {
+        let pointer_id = match input {
+            InputEvent::CursorMoved { .. }
+            | InputEvent::MouseInput { .. }
+            | InputEvent::MouseWheel { .. }
+            | InputEvent::TouchpadPressure { .. } => PointerId::Mouse,
+            InputEvent::Touch { id, .. } => PointerId::Touch(*id),
+            _ => return false,
+        };
+        let Some(capturing_id) = self.pointer_capture_map.iter()
+            .find(|(id, captures)| **id != window_id && captures.contains(&pointer_id))
+            .map(|(id, _)| *id) else {
+            return false;
+        };
+        let input = match input {
+            InputEvent::CursorMoved { x, y } => match self.translate_position(window_id, capturing_id, *x, *y) {
+                Some((x, y)) => InputEvent::CursorMoved { x, y },
+                None => return false,
+            },
+            InputEvent::Touch { id, phase, x, y, force, pen } => match self.translate_position(window_id, capturing_id, *x, *y) {
+                Some((x, y)) => InputEvent::Touch { id: *id, phase: *phase, x, y, force: *force, pen: *pen },
+                None => return false,
+            },
+            input => input.clone(),
+        };
+        if let Some(mut window) = self.remove_window(capturing_id) {
+            window.handle_input(input, self, event_loop);
+            self.add_window(capturing_id, window);
+        }
+        true
+    }
+
+    /// Moves a logical position from the client area of `from` to the one of `to`. Returns `None` where
+    /// the platform doesn't tell where windows are, e.g. on Wayland.
+    fn translate_position(&self, from: WindowId, to: WindowId, x: f32, y: f32) -> Option<(f32, f32)> {
+        let from = self.window_map.get(&from)?.winit_window();
+        let to = self.window_map.get(&to)?.winit_window();
+        let from_origin = from.inner_position().ok()?;
+        let to_origin = to.inner_position().ok()?;
+        let x = (x as f64 * from.scale_factor() + (from_origin.x - to_origin.x) as f64) / to.scale_factor();
+        let y = (y as f64 * from.scale_factor() + (from_origin.y - to_origin.y) as f64) / to.scale_factor();
+        Some((x as f32, y as f32))
+    }
+
+    pub(crate) fn pointer_captures(&self, window_id: WindowId) -> Vec<PointerId> {
+        self.pointer_capture_map.get(&window_id).cloned().unwrap_or_default()
+    }
+
+    fn record_window_state(&mut self, window_id: WindowId, window: &Window) {
+        let Some(manager) = &mut self.window_state_manager else {
+            return;
+        };
+        if let Some((name, _)) = self.window_name_map.iter().find(|(_, id)| **id == window_id) {
+            manager.record(name, window.winit_window());
+        }
+    }
+
+    /// Records every named window and writes the state file if anything changed.
+    pub fn save_window_states(&mut self) {
+        let Some(manager) = &mut self.window_state_manager else {
+            return;
+        };
+        for (name, window_id) in &self.window_name_map {
+            if let Some(window) = self.window_map.get(window_id) {
+                manager.record(name, window.winit_window());
+            }
+        }
+        let _ = manager.save();
+    }
+
+    fn check_and_save_window_state(&mut self) {
+        let now = self.now();
+        if self.window_state_manager.is_some() && now >= self.window_state_saved_at + WINDOW_STATE_SAVE_INTERVAL {
+            self.window_state_saved_at = now;
+            self.save_window_states();
+        }
+    }
+
+    pub fn post_message(&mut self, window_id: WindowId, message: WindowMessage) {
+        self.message_queue.push_back((Some(window_id), message));
+    }
+
+    /// Queues the message for every window except its sender.
+    pub fn broadcast_message(&mut self, message: WindowMessage) {
+        self.message_queue.push_back((None, message));
+    }
+
+    /// Starts over if the window was already being recorded.
+    pub fn start_recording(&mut self, window_id: WindowId) {
+        self.recording_map.insert(window_id, InputRecording::default());
+    }
+
+    pub fn stop_recording(&mut self, window_id: WindowId) -> Option<InputRecording> {
+        self.recording_map.remove(&window_id)
+    }
+
+    pub(crate) fn record_input(&mut self, window_id: WindowId, time: Duration, event: &InputEvent) {
+        if self.replaying {
+            return;
+        }
+        if let Some(recording) = self.recording_map.get_mut(&window_id) {
+            recording.events.push(RecordedInput {
+                time,
+                event: event.clone(),
+            });
+        }
+    }
+
+    /// Replaces the replay running in the window, if any.
+    pub fn replay(&mut self, window_id: WindowId, recording: InputRecording, speed: ReplaySpeed) {
+        self.replay_map.insert(window_id, Replay::new(recording, speed));
+    }
+
+    pub fn check_and_run_replay(&mut self, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        if self.replay_map.is_empty() {
+            return;
+        }
+        let now = self.now();
+        let window_ids: Vec<WindowId> = self.replay_map.keys().copied().collect();
+        for window_id in window_ids {
+            let events = match (self.replay_map.get_mut(&window_id), self.window_map.get(&window_id)) {
+                (Some(replay), Some(window)) => replay.take_due(window.created_at(), now),
+                _ => {
+                    self.replay_map.remove(&window_id);
+                    continue;
+                }
+            };
+            self.replaying = true;
+            for event in events {
+                self.dispatch_input(window_id, event, event_loop);
+            }
+            self.replaying = false;
+            if self.replay_map.get(&window_id).is_some_and(|replay| replay.is_finished()) {
+                self.replay_map.remove(&window_id);
+            }
+        }
+    }
+
+    /// Queues the event for the next event loop iteration, where it takes the same way as events
+    /// from the system: through popups, modal windows, shortcuts and recording to the window's UI.
+    pub fn inject_event(&mut self, window_id: WindowId, event: impl Into<SyntheticEvent>) {
+        self.synthetic_queue.push_back((window_id, event.into()));
+    }
+
+    pub fn check_and_run_synthetic_events(&mut self, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        if self.synthetic_queue.is_empty() {
             return;
         }
-        let now = Instant::now();
-        let ids: Vec<_> = self.interval_map.iter().filter(|(_, &(last, time, _))| now >= last + time).map(|(id, _)| id.clone()).collect();
-        for id in ids {
-            let (_, time, f) = self.interval_map.remove(&id).unwrap();
-            f(id, self, event_loop);
-            self.interval_map.insert(id, (now, time, f));
+        let events: Vec<_> = self.synthetic_queue.drain(..).collect();
+        for (window_id, event) in events {
+            let Some(scale_factor) = self.window_map.get(&window_id).map(|window| window.scale_factor()) else {
+                continue;
+            };
+            match event.into_delivery(scale_factor) {
+                Delivery::Input(input) => self.dispatch_input(window_id, input, event_loop),
+                Delivery::Window(event) => self.dispatch_window_event(window_id, event, event_loop),
+            }
+        }
+    }
+
+    /// Replaces the backend gamepads are read from, e.g. with a `MockGamepadBackend` in tests.
+    #[cfg(feature = "gamepad")]
+    pub fn set_gamepad_backend(&mut self, backend: impl GamepadBackend + 'static) {
+        let waker = self.gamepad_waker();
+        self.gamepads.set_backend(Box::new(backend), waker);
+    }
+
+    /// Starts reading the platform's gamepads, until `set_gamepad_backend` replaces the backend.
+    #[cfg(feature = "gamepad")]
+    fn start_default_gamepad_backend(&mut self) {
+        if let Some(backend) = crate::gamepad::default_backend() {
+            let waker = self.gamepad_waker();
+            self.gamepads.set_backend(backend, waker);
+        }
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn gamepad_waker(&self) -> GamepadWaker {
+        let event_loop_proxy = self.event_loop_proxy.clone();
+        Box::new(move || {
+            // Fails once the event loop has exited, nobody is left to read the gamepads then.
+            let _ = event_loop_proxy.send_event(CustomEvent::GamepadInput);
+        })
+    }
+
+    /// Gamepads connected at the moment, in the order they were connected.
+    #[cfg(feature = "gamepad")]
+    pub fn gamepads(&self) -> &[Gamepad] {
+        self.gamepads.gamepads()
+    }
+
+    /// Gamepads work whatever window is focused, even one of another application, so their input
+    /// only goes to our focused window and is dropped while none has the focus.
+    #[cfg(feature = "gamepad")]
+    pub fn check_and_run_gamepads(&mut self, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        let now = self.now();
+        for (window_id, event) in self.gamepads.poll(now, self.focused_window) {
+            if let Some(mut window) = self.remove_window(window_id) {
+                window.on_gamepad(event, self, event_loop);
+                self.add_window(window_id, window);
+            }
+        }
+    }
+
+    fn dispatch_window_event(&mut self, window_id: WindowId, event: WindowEvent, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        self.track_focus(window_id, &event);
+        let Some(scale_factor) = self.window_map.get(&window_id).map(|window| window.scale_factor()) else {
+            return;
+        };
+        let blocked = match InputEvent::from_window_event(&event, scale_factor) {
+            Some(input) => self.redirect_captured_input(window_id, &input, event_loop)
+                || self.handle_popup_dismissal(window_id, &input)
+                || self.block_modal_input(window_id, &input),
+            None => self.block_modal_event(window_id, &event),
+        };
+        if blocked {
+            return;
+        }
+        if let Some(mut window) = self.remove_window(window_id) {
+            window.handle_event(event, self, event_loop);
+            self.add_window(window_id, window);
+        }
+    }
+
+    fn dispatch_input(&mut self, window_id: WindowId, input: InputEvent, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        if self.redirect_captured_input(window_id, &input, event_loop) {
+            return;
+        }
+        if self.handle_popup_dismissal(window_id, &input) || self.block_modal_input(window_id, &input) {
+            return;
+        }
+        if let Some(mut window) = self.remove_window(window_id) {
+            window.handle_input(input, self, event_loop);
+            self.add_window(window_id, window);
+        }
+    }
+
+    pub fn check_and_run_message(&mut self, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        if self.message_queue.is_empty() {
+            return;
+        }
+        // Messages sent while delivering are left for the next iteration, so windows replying to each other can't starve the loop.
+        let messages: Vec<_> = self.message_queue.drain(..).collect();
+        for (target, message) in messages {
+            let window_ids = match target {
+                Some(window_id) => vec![window_id],
+                None => self.window_map.keys().copied().filter(|id| Some(*id) != message.sender()).collect(),
+            };
+            for window_id in window_ids {
+                if let Some(mut window) = self.remove_window(window_id) {
+                    window.on_message(message.clone(), self, event_loop);
+                    self.add_window(window_id, window);
+                }
+            }
         }
     }
 
@@ -102,63 +598,496 @@ impl Application {
         self.window_map.len()
     }
 
+    pub fn set_parent_window(&mut self, window_id: WindowId, parent_id: WindowId) {
+        self.window_tree.set_parent(window_id, parent_id);
+    }
+
+    pub fn parent_window(&self, window_id: WindowId) -> Option<WindowId> {
+        self.window_tree.parent(window_id)
+    }
+
+    pub fn child_windows(&self, window_id: WindowId) -> Vec<WindowId> {
+        self.window_tree.children(window_id)
+    }
+
+    pub fn set_modal_window(&mut self, owner_id: WindowId, window_id: WindowId) {
+        self.window_tree.set_modal(owner_id, window_id);
+    }
+
+    pub fn modal_window(&self, owner_id: WindowId) -> Option<WindowId> {
+        self.window_tree.modal(owner_id)
+    }
+
+    pub fn set_modal_result(&mut self, window_id: WindowId, result: Box<dyn Any>) {
+        self.window_tree.set_modal_result(window_id, result);
+    }
+
+    pub fn focused_window(&self) -> Option<WindowId> {
+        self.focused_window
+    }
+
+    /// Open windows in the order they last had focus, the most recent one last.
+    pub fn focus_history(&self) -> &[WindowId] {
+        &self.focus_history
+    }
+
+    fn track_focus(&mut self, window_id: WindowId, event: &WindowEvent) {
+        match event {
+            WindowEvent::Focused(true) => {
+                self.focused_window = Some(window_id);
+                self.focus_history.retain(|id| *id != window_id);
+                self.focus_history.push(window_id);
+            }
+            WindowEvent::Focused(false) if self.focused_window == Some(window_id) => {
+                self.focused_window = None;
+            }
+            _ => {}
+        }
+        if let WindowEvent::Focused(focused) = event {
+            if !self.window_tree.popups().is_empty() {
+                // Focus moving between our own windows sends `Focused(false)` and `Focused(true)` in the same
+                // iteration, popups are dismissed in `check_popup_focus` once both have been seen.
+                self.popup_focus_lost = !focused;
+            }
+        }
+    }
+
+    pub fn add_popup_window(&mut self, window_id: WindowId) {
+        self.window_tree.add_popup(window_id);
+    }
+
+    pub fn dismiss_popups(&mut self) {
+        for window_id in self.window_tree.popups().to_vec() {
+            self.request_close_window(window_id);
+        }
+    }
+
+    /// Closes popups on a click outside of them, and the top one on Escape pressed in it or in one of
+    /// the windows it opened from. Returns `true` if the input was used to dismiss a popup and must not reach the window.
+    fn handle_popup_dismissal(&mut self, window_id: WindowId, input: &InputEvent) -> bool {
+        if self.window_tree.popups().is_empty() {
+            return false;
+        }
+        match input {
+            InputEvent::MouseInput { pressed: true, .. } | InputEvent::Touch { phase: TouchPhase::Started, .. } => {
+                self.dismiss_popups_outside(window_id);
+                false
+            }
+            InputEvent::Key { key: Key::Named(NamedKey::Escape), pressed: true, .. } => {
+                match self.window_tree.popups().last().copied() {
+                    Some(popup_id) if self.window_tree.ancestors(popup_id).contains(&window_id) => {
+                        self.request_close_window(popup_id);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn dismiss_popups_outside(&mut self, window_id: WindowId) {
+        for popup_id in self.window_tree.popups_outside(window_id) {
+            self.request_close_window(popup_id);
+        }
+    }
+
+    fn check_popup_focus(&mut self) {
+        if self.popup_focus_lost {
+            self.popup_focus_lost = false;
+            self.dismiss_popups();
+        }
+    }
+
+    fn flush_file_drags(&mut self, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        let window_ids: Vec<WindowId> = self.window_map.iter()
+            .filter(|(_, window)| window.has_pending_file_drag())
+            .map(|(window_id, _)| *window_id)
+            .collect();
+        for window_id in window_ids {
+            if let Some(mut window) = self.remove_window(window_id) {
+                window.flush_file_drag(self, event_loop);
+                self.add_window(window_id, window);
+            }
+        }
+    }
+
+    fn focus_window(&self, window_id: WindowId) {
+        if let Some(window) = self.window_map.get(&window_id) {
+            window.focus_window();
+        }
+    }
+
+    /// Returns `true` if the event must not reach the window because a modal window is open on it.
+    /// Focusing a blocked window brings its modal window to the front instead.
+    fn block_modal_event(&mut self, window_id: WindowId, event: &WindowEvent) -> bool {
+        let Some(modal_id) = self.window_tree.blocking_modal(window_id) else {
+            return false;
+        };
+        if matches!(event, WindowEvent::Focused(true)) {
+            self.focus_window(modal_id);
+        }
+        matches!(
+            event,
+            WindowEvent::CloseRequested
+                | WindowEvent::AxisMotion { .. }
+                | WindowEvent::DroppedFile(_)
+                | WindowEvent::HoveredFile(_)
+                | WindowEvent::HoveredFileCancelled
+        )
+    }
+
+    /// Returns `true` if a modal window is open on the window, which then gets no input at all.
+    /// Clicking a blocked window brings its modal window to the front instead.
+    fn block_modal_input(&mut self, window_id: WindowId, input: &InputEvent) -> bool {
+        let Some(modal_id) = self.window_tree.blocking_modal(window_id) else {
+            return false;
+        };
+        if matches!(input, InputEvent::MouseInput { pressed: true, .. }) {
+            self.focus_window(modal_id);
+        }
+        true
+    }
+
     pub fn request_close_window(&mut self, window_id: WindowId) {
-        self.event_loop_proxy.send_event(CustomEvent::CloseWindow(window_id)).unwrap();
+        // Fails once the event loop has exited, which closed every window.
+        let _ = self.event_loop_proxy.send_event(CustomEvent::CloseWindow(window_id));
     }
+
     pub fn request_exit(&mut self) {
-        self.event_loop_proxy.send_event(CustomEvent::Exit).unwrap();
+        // Fails once the event loop has exited already.
+        let _ = self.event_loop_proxy.send_event(CustomEvent::Exit);
     }
 
-    fn do_close_window(&mut self, event_loop: &EventLoopWindowTarget<CustomEvent>, window_id: WindowId) {
-        if let Some(window) = self.remove_window(window_id) {
-            window.on_close();
+    /// Closes the window with the windows it owns, each of them before its owner.
+    fn do_close_window(&mut self, event_handler: &EventHandler, event_loop: &EventLoopWindowTarget<CustomEvent>, window_id: WindowId) {
+        if !self.window_map.contains_key(&window_id) {
+            return;
         }
-        if self.count_window() == 0 {
-            event_loop.exit();
+        for window_id in self.window_tree.close_order(window_id) {
+            self.close_single_window(event_handler, event_loop, window_id);
         }
     }
-}
 
-pub fn launch(event_handler: EventHandler) {
-    let event_loop: EventLoop<CustomEvent> = EventLoopBuilder::with_user_event().build().unwrap();
-    event_loop.set_control_flow(ControlFlow::Poll);
-    let event_loop_proxy = event_loop.create_proxy();
-    let mut application = Application::new(event_loop_proxy);
+    fn close_single_window(&mut self, event_handler: &EventHandler, event_loop: &EventLoopWindowTarget<CustomEvent>, window_id: WindowId) {
+        if let Some(mut window) = self.remove_window(window_id) {
+            self.record_window_state(window_id, &window);
+            if let Some(manager) = &mut self.window_state_manager {
+                let _ = manager.save();
+            }
+            self.animation_frame_map.remove(&window_id);
+            self.animator.remove_window(window_id);
+            window.on_close(self, event_loop);
+            self.window_name_map.retain(|_, id| *id != window_id);
+            self.focus_history.retain(|id| *id != window_id);
+            self.fullscreen_restore_map.remove(&window_id);
+            self.pointer_capture_map.remove(&window_id);
+            self.window_commands_map.remove(&window_id);
+            self.recording_map.remove(&window_id);
+            self.replay_map.remove(&window_id);
+            self.synthetic_queue.retain(|(id, _)| *id != window_id);
+            if self.focused_window == Some(window_id) {
+                self.focused_window = None;
+            }
+            if let Some((owner_id, result)) = self.window_tree.remove(window_id) {
+                if let Some(mut owner) = self.remove_window(owner_id) {
+                    owner.on_modal_close(window_id, result, self, event_loop);
+                    self.add_window(owner_id, owner);
+                }
+            }
+            if self.lifecycle.runs_last_window_closed(self.window_map.len()) {
+                event_handler.on_last_window_closed(&mut Context::new(self, event_loop));
+            }
+        }
+    }
 
-    event_loop.run(move |event, event_loop| {
-        application.check_and_run_timer(event_loop);
-        application.check_and_run_interval(event_loop);
+    /// Handles one event of the loop driven by `run`.
+    fn handle_loop_event(&mut self, event_handler: &EventHandler, event: Event<CustomEvent>, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        self.check_and_run_timer(event_loop);
+        self.check_and_run_interval(event_loop);
+        self.check_and_run_message(event_loop);
+        self.check_and_run_replay(event_loop);
+        self.check_and_run_synthetic_events(event_loop);
+        #[cfg(feature = "gamepad")]
+        self.check_and_run_gamepads(event_loop);
         match event {
             Event::NewEvents(StartCause::Init) => {
-                let mut context = Context::new(&mut application, event_loop);
+                let mut context = Context::new(self, event_loop);
                 event_handler.on_init(&mut context);
+                event_handler.open_configured_windows(&mut context);
             }
             Event::WindowEvent { event, window_id } => {
-                if let winit::event::WindowEvent::CloseRequested = event {
-                    application.do_close_window(event_loop, window_id);
-                }
-                if let Some(mut window) = application.window_map.remove(&window_id) {
-                    window.handle_event(event, &mut application, event_loop);
-                    application.window_map.insert(window_id, window);
-                }
+                self.dispatch_window_event(window_id, event, event_loop);
+            }
+            Event::DeviceEvent { device_id, event } => {
+                let mut context = Context::new(self, event_loop);
+                event_handler.on_device_event(&mut context, device_id, event);
             }
-            Event::DeviceEvent { .. } => {}
             Event::UserEvent(event) => {
                 match event {
                     CustomEvent::CloseWindow(window_id) => {
-                        application.do_close_window(event_loop, window_id);
+                        self.do_close_window(event_handler, event_loop, window_id);
+                    }
+                    CustomEvent::SecondInstance(args) => {
+                        let mut context = Context::new(self, event_loop);
+                        event_handler.on_second_instance(&mut context, args);
                     }
                     CustomEvent::Exit => {
-                        event_loop.exit();
+                        self.do_exit(event_handler, event_loop);
                     }
+                    #[cfg(feature = "gamepad")]
+                    CustomEvent::GamepadInput => {}
                 }
             }
-
-            Event::LoopExiting => {}
+            Event::Resumed => {
+                let mut context = Context::new(self, event_loop);
+                event_handler.on_resumed(&mut context);
+            }
+            Event::Suspended => {
+                let mut context = Context::new(self, event_loop);
+                event_handler.on_suspended(&mut context);
+            }
+            Event::AboutToWait => {
+                self.check_popup_focus();
+                self.flush_file_drags(event_loop);
+                self.check_and_save_window_state();
+                let mut context = Context::new(self, event_loop);
+                event_handler.on_about_to_wait(&mut context);
+                event_loop.set_control_flow(self.control_flow());
+            }
+            Event::MemoryWarning => {
+                let mut context = Context::new(self, event_loop);
+                event_handler.on_memory_warning(&mut context);
+            }
+            Event::LoopExiting => {
+                self.save_window_states();
+                let mut context = Context::new(self, event_loop);
+                event_handler.on_loop_exiting(&mut context);
+            }
             Event::NewEvents(StartCause::ResumeTimeReached { .. }) => {}
             Event::NewEvents(StartCause::WaitCancelled { .. }) => {}
             Event::NewEvents(StartCause::Poll) => {}
-            _ => {}
         }
+    }
+
+    fn do_exit(&mut self, event_handler: &EventHandler, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        if self.lifecycle.is_exiting() {
+            return;
+        }
+        let allowed = event_handler.on_exit_requested(&mut Context::new(self, event_loop));
+        if !self.lifecycle.request_exit(allowed, self.window_map.len()) {
+            return;
+        }
+        // Windows still open get `UIEvent::WindowClose` like any closed window, without a chance to
+        // refuse it.
+        let window_ids: Vec<WindowId> = self.window_map.keys().copied().collect();
+        for window_id in window_ids {
+            self.do_close_window(event_handler, event_loop, window_id);
+        }
+        event_loop.exit();
+    }
+}
+
+pub fn launch(event_handler: EventHandler) {
+    let event_loop: EventLoop<CustomEvent> = EventLoopBuilder::with_user_event().build().unwrap();
+    run(event_loop, event_handler);
+}
+
+/// Like `launch`, but only one instance per `app_id` runs at a time. A later instance forwards its
+/// `InstanceArgs` to the running one, which receives them in the second instance handler, and returns.
+/// Only implemented on Unix, elsewhere every instance runs.
+///
+/// Fails before anything runs if it can't tell whether another instance is running, for example when the
+/// runtime directory isn't writable. `launch` an event handler then to run regardless.
+pub fn launch_single_instance(app_id: &str, event_handler: EventHandler) -> Result<(), SingleInstanceError> {
+    let instance = match single_instance::acquire(app_id, &InstanceArgs::from_env())? {
+        Acquired::First(instance) => instance,
+        Acquired::Forwarded => return Ok(()),
+    };
+    let event_loop: EventLoop<CustomEvent> = EventLoopBuilder::with_user_event().build().unwrap();
+    let event_loop_proxy = event_loop.create_proxy();
+    instance.listen(move |args| event_loop_proxy.send_event(CustomEvent::SecondInstance(args)).is_ok())?;
+    run(event_loop, event_handler);
+    Ok(())
+}
+
+fn run(event_loop: EventLoop<CustomEvent>, event_handler: EventHandler) {
+    event_loop.set_control_flow(ControlFlow::Wait);
+    let event_loop_proxy = event_loop.create_proxy();
+    let mut application = Application::new(event_loop_proxy);
+    input::load_double_click_time();
+    #[cfg(feature = "gamepad")]
+    application.start_default_gamepad_backend();
+
+    event_loop.run(move |event, event_loop| {
+        application.handle_loop_event(&event_handler, event, event_loop);
     }).unwrap();
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use skia_safe::Canvas;
+    use winit::dpi::LogicalPosition;
+    use crate::animation::tween::Tween;
+    use crate::clock::VirtualClock;
+    use winit::platform::run_on_demand::EventLoopExtRunOnDemand;
+    use winit::platform::x11::EventLoopBuilderExtX11;
+    use crate::context::window_context::WindowContext;
+    use crate::event::input::PointerButton;
+    use crate::event::ui_event::UIEvent;
+    use crate::ui::UI;
+    use super::*;
+
+    /// winit allows a single event loop per process, so the tests opening windows share one and run in turn.
+    #[test]
+    #[ignore = "opens windows, needs a display"]
+    fn with_a_display() {
+        let mut event_loop = EventLoopBuilder::with_user_event().with_any_thread(true).build().unwrap();
+        owner_opens_windows_from_its_handler(&mut event_loop);
+        prevented_close_keeps_the_window_open(&mut event_loop);
+        virtual_time_runs_on_tick(&mut event_loop);
+        crate::window_control::tests::controls_apply_to_the_window(&event_loop);
+    }
+
+    /// Runs the app until it exits.
+    fn run_app(event_loop: &mut EventLoop<CustomEvent>, event_handler: EventHandler) {
+        event_loop.set_control_flow(ControlFlow::Wait);
+        let mut application = Application::new(event_loop.create_proxy());
+        event_loop.run_on_demand(|event, event_loop| {
+            application.handle_loop_event(&event_handler, event, event_loop);
+        }).unwrap();
+    }
+
+    fn hidden() -> WindowBuilder {
+        WindowBuilder::new().with_visible(false)
+    }
+
+    struct Dialog;
+
+    impl UI for Dialog {
+        fn draw(&mut self, _canvas: &Canvas) {}
+
+        fn handle_event(&mut self, _event: UIEvent, _event_context: &mut WindowContext) {}
+    }
+
+    /// Opens windows from its own handler, while it is out of the window map.
+    struct Owner {
+        opened: Rc<RefCell<Vec<bool>>>,
+    }
+
+    impl UI for Owner {
+        fn draw(&mut self, _canvas: &Canvas) {}
+
+        fn handle_event(&mut self, event: UIEvent, event_context: &mut WindowContext) {
+            if let UIEvent::PointerDown(_) = event {
+                let mut opened = self.opened.borrow_mut();
+                opened.push(event_context.open_popup_window(LogicalPosition::new(10.0, 10.0), hidden(), Dialog).is_some());
+                opened.push(event_context.open_modal_window(hidden(), Dialog).is_some());
+                // The owner already has a modal window.
+                opened.push(event_context.open_modal_window(hidden(), Dialog).is_some());
+                event_context.application().exit();
+            }
+        }
+    }
+
+    fn owner_opens_windows_from_its_handler(event_loop: &mut EventLoop<CustomEvent>) {
+        let opened = Rc::new(RefCell::new(vec![]));
+        let mut event_handler = EventHandler::new();
+        let owner_opened = opened.clone();
+        event_handler.add_init_handler(move |context| {
+            let owner = Owner { opened: owner_opened.clone() };
+            let window_id = context.new_window(hidden(), owner).unwrap().id();
+            context.inject_events(window_id, SyntheticEvent::click(10.0, 10.0, PointerButton::Primary));
+        });
+        run_app(event_loop, event_handler);
+        assert_eq!(*opened.borrow(), vec![true, true, false]);
+    }
+
+    /// Refuses to close the first time, then closes and asks to close once more.
+    struct Unsaved {
+        log: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl UI for Unsaved {
+        fn draw(&mut self, _canvas: &Canvas) {}
+
+        fn handle_event(&mut self, event: UIEvent, event_context: &mut WindowContext) {
+            match event {
+                UIEvent::CloseRequested => {
+                    let first = !self.log.borrow().contains(&"close requested");
+                    self.log.borrow_mut().push("close requested");
+                    if first {
+                        event_context.prevent_close();
+                        // Only delivered if the window is still open.
+                        event_context.inject_event(WindowEvent::CloseRequested);
+                    } else {
+                        event_context.close_window();
+                    }
+                }
+                UIEvent::WindowClose => self.log.borrow_mut().push("closed"),
+                _ => {}
+            }
+        }
+    }
+
+    fn prevented_close_keeps_the_window_open(event_loop: &mut EventLoop<CustomEvent>) {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut event_handler = EventHandler::new();
+        let ui_log = log.clone();
+        event_handler.add_init_handler(move |context| {
+            let window_id = context.new_window(hidden(), Unsaved { log: ui_log.clone() }).unwrap().id();
+            context.inject_event(window_id, WindowEvent::CloseRequested);
+        });
+        run_app(event_loop, event_handler);
+        assert_eq!(*log.borrow(), vec!["close requested", "close requested", "closed"]);
+    }
+
+    /// Starts an animation when clicked.
+    struct Animated {
+        log: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl UI for Animated {
+        fn draw(&mut self, _canvas: &Canvas) {}
+
+        fn handle_event(&mut self, event: UIEvent, event_context: &mut WindowContext) {
+            if let UIEvent::PointerDown(_) = event {
+                self.log.borrow_mut().push("clicked");
+                let log = self.log.clone();
+                event_context.animate(Tween::new(0.0f32, 1.0, Duration::from_millis(100)))
+                    .on_end(move |_| log.borrow_mut().push("animation ended"));
+            }
+        }
+    }
+
+    fn virtual_time_runs_on_tick(event_loop: &mut EventLoop<CustomEvent>) {
+        let log = Rc::new(RefCell::new(vec![]));
+        let clock = VirtualClock::new();
+        let mut event_handler = EventHandler::new();
+        let handler_log = log.clone();
+        event_handler.add_init_handler(move |context| {
+            let timer_log = handler_log.clone();
+            context.set_timer(Duration::from_secs(1), move |_, _, _| timer_log.borrow_mut().push("timer"));
+            let window_id = context.new_window(hidden(), Animated { log: handler_log.clone() }).unwrap().id();
+            context.inject_events(window_id, SyntheticEvent::click(10.0, 10.0, PointerButton::Primary));
+        });
+        event_loop.set_control_flow(ControlFlow::Wait);
+        let mut application = Application::new(event_loop.create_proxy());
+        application.set_clock(clock.clone());
+        event_loop.run_on_demand(|event, event_loop| {
+            let about_to_wait = matches!(event, Event::AboutToWait);
+            application.handle_loop_event(&event_handler, event, event_loop);
+            if about_to_wait && log.borrow().contains(&"clicked") {
+                // The timer is a second away in virtual time, which doesn't pass while the loop sleeps.
+                assert_eq!(application.control_flow(), ControlFlow::Wait);
+                assert_eq!(*log.borrow(), vec!["clicked"]);
+                clock.advance(Duration::from_secs(1));
+                application.tick(event_loop);
+                event_loop.exit();
+            }
+        }).unwrap();
+        assert_eq!(*log.borrow(), vec!["clicked", "timer", "animation ended"]);
+    }
+}
@@ -0,0 +1,218 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use skia_safe::image::CachingHint;
+use skia_safe::{images, AlphaType, ColorType, Data, EncodedImageFormat, Image, ImageInfo};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardError {
+    /// The clipboard holds nothing in the requested format.
+    Empty,
+    /// The system clipboard can't be reached, e.g. without a display server.
+    Unavailable(String),
+    /// The image couldn't be read, decoded or encoded.
+    InvalidImage,
+    /// The current platform has no such clipboard.
+    Unsupported,
+    Other(String),
+}
+
+impl Display for ClipboardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClipboardError::Empty => write!(f, "the clipboard holds nothing in the requested format"),
+            ClipboardError::Unavailable(error) => write!(f, "clipboard unavailable: {}", error),
+            ClipboardError::InvalidImage => write!(f, "invalid clipboard image"),
+            ClipboardError::Unsupported => write!(f, "clipboard not supported on this platform"),
+            ClipboardError::Other(error) => write!(f, "clipboard error: {}", error),
+        }
+    }
+}
+
+impl Error for ClipboardError {}
+
+impl From<arboard::Error> for ClipboardError {
+    fn from(error: arboard::Error) -> Self {
+        match error {
+            arboard::Error::ContentNotAvailable => ClipboardError::Empty,
+            arboard::Error::ClipboardNotSupported => ClipboardError::Unsupported,
+            arboard::Error::ConversionFailure => ClipboardError::InvalidImage,
+            error => ClipboardError::Other(error.to_string()),
+        }
+    }
+}
+
+/// The system clipboard, reached with `Context::clipboard`.
+/// Connects to the system the first time it is used.
+///
+/// Holds text, HTML and images. Formats of the application's own, such as `application/x-my-app-shapes`,
+/// aren't supported: serialize them to text or HTML to copy them between windows or applications.
+pub struct Clipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    pub(crate) fn new() -> Self {
+        Self {
+            inner: None,
+        }
+    }
+
+    fn inner(&mut self) -> Result<&mut arboard::Clipboard, ClipboardError> {
+        if self.inner.is_none() {
+            let clipboard = arboard::Clipboard::new().map_err(|error| ClipboardError::Unavailable(error.to_string()))?;
+            self.inner = Some(clipboard);
+        }
+        Ok(self.inner.as_mut().unwrap())
+    }
+
+    pub fn text(&mut self) -> Result<String, ClipboardError> {
+        Ok(self.inner()?.get_text()?)
+    }
+
+    pub fn set_text(&mut self, text: impl Into<String>) -> Result<(), ClipboardError> {
+        Ok(self.inner()?.set_text(text.into())?)
+    }
+
+    pub fn html(&mut self) -> Result<String, ClipboardError> {
+        Ok(self.inner()?.get().html()?)
+    }
+
+    /// `alt_text` is pasted by applications that don't accept HTML.
+    pub fn set_html(&mut self, html: impl Into<String>, alt_text: Option<&str>) -> Result<(), ClipboardError> {
+        Ok(self.inner()?.set_html(html.into(), alt_text.map(String::from))?)
+    }
+
+    pub fn image(&mut self) -> Result<Image, ClipboardError> {
+        image_from_data(&self.inner()?.get_image()?)
+    }
+
+    /// `image` must be a raster image, read back GPU images with `Image::make_raster_image` first.
+    pub fn set_image(&mut self, image: &Image) -> Result<(), ClipboardError> {
+        let data = image_data(image)?;
+        Ok(self.inner()?.set_image(data)?)
+    }
+
+    /// The clipboard image encoded as PNG.
+    pub fn png(&mut self) -> Result<Vec<u8>, ClipboardError> {
+        encode_png(&self.image()?)
+    }
+
+    pub fn set_png(&mut self, png: &[u8]) -> Result<(), ClipboardError> {
+        self.set_image(&decode_png(png)?)
+    }
+
+    /// Text selected in any application, pasted with the middle mouse button on X11 and Wayland.
+    #[cfg(all(unix, not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))))]
+    pub fn primary_text(&mut self) -> Result<String, ClipboardError> {
+        use arboard::{GetExtLinux, LinuxClipboardKind};
+        Ok(self.inner()?.get().clipboard(LinuxClipboardKind::Primary).text()?)
+    }
+
+    #[cfg(not(all(unix, not(any(target_os = "macos", target_os = "android", target_os = "emscripten")))))]
+    pub fn primary_text(&mut self) -> Result<String, ClipboardError> {
+        Err(ClipboardError::Unsupported)
+    }
+
+    /// Call it when the user selects text, so that it can be pasted elsewhere with the middle mouse button.
+    #[cfg(all(unix, not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))))]
+    pub fn set_primary_text(&mut self, text: impl Into<String>) -> Result<(), ClipboardError> {
+        use arboard::{LinuxClipboardKind, SetExtLinux};
+        Ok(self.inner()?.set().clipboard(LinuxClipboardKind::Primary).text(text.into())?)
+    }
+
+    #[cfg(not(all(unix, not(any(target_os = "macos", target_os = "android", target_os = "emscripten")))))]
+    pub fn set_primary_text(&mut self, _text: impl Into<String>) -> Result<(), ClipboardError> {
+        Err(ClipboardError::Unsupported)
+    }
+}
+
+/// Clipboard images are unpremultiplied RGBA, rows without padding.
+fn image_from_data(data: &arboard::ImageData) -> Result<Image, ClipboardError> {
+    let info = ImageInfo::new((data.width as i32, data.height as i32), ColorType::RGBA8888, AlphaType::Unpremul, None);
+    images::raster_from_data(&info, Data::new_copy(&data.bytes), data.width * 4).ok_or(ClipboardError::InvalidImage)
+}
+
+fn image_data(image: &Image) -> Result<arboard::ImageData<'static>, ClipboardError> {
+    let width = image.width() as usize;
+    let height = image.height() as usize;
+    let info = ImageInfo::new((image.width(), image.height()), ColorType::RGBA8888, AlphaType::Unpremul, None);
+    let mut bytes = vec![0u8; width * height * 4];
+    if !image.read_pixels(&info, &mut bytes, width * 4, (0, 0), CachingHint::Allow) {
+        return Err(ClipboardError::InvalidImage);
+    }
+    Ok(arboard::ImageData {
+        width,
+        height,
+        bytes: Cow::Owned(bytes),
+    })
+}
+
+fn encode_png(image: &Image) -> Result<Vec<u8>, ClipboardError> {
+    let data = image.encode(None, EncodedImageFormat::PNG, None).ok_or(ClipboardError::InvalidImage)?;
+    Ok(data.as_bytes().to_vec())
+}
+
+fn decode_png(png: &[u8]) -> Result<Image, ClipboardError> {
+    Image::from_encoded(Data::new_copy(png)).ok_or(ClipboardError::InvalidImage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2×2 image with a translucent pixel, which must come back unchanged rather than premultiplied.
+    fn pixels() -> arboard::ImageData<'static> {
+        arboard::ImageData {
+            width: 2,
+            height: 2,
+            bytes: Cow::Owned(vec![
+                255, 0, 0, 255,
+                0, 255, 0, 255,
+                0, 0, 255, 255,
+                255, 255, 255, 128,
+            ]),
+        }
+    }
+
+    fn rgba(data: &arboard::ImageData) -> (usize, usize, Vec<u8>) {
+        (data.width, data.height, data.bytes.to_vec())
+    }
+
+    #[test]
+    fn images_round_trip_through_clipboard_pixels() {
+        let image = image_from_data(&pixels()).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 2));
+        assert_eq!(rgba(&image_data(&image).unwrap()), rgba(&pixels()));
+    }
+
+    #[test]
+    fn images_round_trip_through_png() {
+        let png = encode_png(&image_from_data(&pixels()).unwrap()).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        assert_eq!(rgba(&image_data(&decode_png(&png).unwrap()).unwrap()), rgba(&pixels()));
+    }
+
+    #[test]
+    fn truncated_pixels_and_png_are_invalid() {
+        let truncated = arboard::ImageData {
+            bytes: Cow::Owned(vec![0; 8]),
+            ..pixels()
+        };
+        assert_eq!(image_from_data(&truncated).err(), Some(ClipboardError::InvalidImage));
+        assert_eq!(decode_png(b"\x89PNG\r\n").err(), Some(ClipboardError::InvalidImage));
+    }
+
+    #[test]
+    #[ignore = "uses the system clipboard, needs a display"]
+    fn html_and_images_round_trip_through_the_system_clipboard() {
+        let mut clipboard = Clipboard::new();
+        clipboard.set_html("<b>bold</b> move", Some("bold move")).unwrap();
+        assert_eq!(clipboard.html().unwrap(), "<b>bold</b> move");
+        assert_eq!(clipboard.text().unwrap(), "bold move");
+
+        clipboard.set_image(&image_from_data(&pixels()).unwrap()).unwrap();
+        assert_eq!(rgba(&image_data(&clipboard.image().unwrap()).unwrap()), rgba(&pixels()));
+        assert_eq!(clipboard.html().err(), Some(ClipboardError::Empty));
+    }
+}
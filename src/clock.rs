@@ -0,0 +1,59 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Time source of an `Application`, used for timers, intervals, animation frames and tweens.
+pub trait Clock {
+    fn now(&self) -> Instant;
+
+    /// Whether the clock only moves when told to. The event loop doesn't sleep until the deadlines of
+    /// such a clock, they are run by `Application::tick` after advancing it.
+    fn is_virtual(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when advanced, so timer-driven behavior can be tested without sleeping.
+/// Clones share the same time, keep one to advance it after handing another to `Application::set_clock`,
+/// then call `Application::tick` to run what became due.
+#[derive(Debug, Clone)]
+pub struct VirtualClock {
+    now: Rc<Cell<Instant>>,
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self {
+            now: Rc::new(Cell::new(Instant::now())),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+
+    fn is_virtual(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,57 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use serde::de::DeserializeOwned;
+
+/// Reads a `.toml` or `.json` file, chosen by its extension.
+pub(crate) fn load<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, ConfigFileError> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(ConfigFileError::Io)?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => from_toml(&content),
+        Some("json") => from_json(&content),
+        _ => Err(ConfigFileError::UnknownFormat),
+    }
+}
+
+pub(crate) fn from_toml<T: DeserializeOwned>(content: &str) -> Result<T, ConfigFileError> {
+    toml::from_str(content).map_err(ConfigFileError::Toml)
+}
+
+pub(crate) fn from_json<T: DeserializeOwned>(content: &str) -> Result<T, ConfigFileError> {
+    serde_json::from_str(content).map_err(ConfigFileError::Json)
+}
+
+/// Why a window config or key bindings file couldn't be read.
+#[derive(Debug)]
+pub enum ConfigFileError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    /// The file extension is neither `toml` nor `json`.
+    UnknownFormat,
+}
+
+impl Display for ConfigFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigFileError::Io(error) => write!(f, "failed to read config file: {}", error),
+            ConfigFileError::Toml(error) => write!(f, "invalid config file: {}", error),
+            ConfigFileError::Json(error) => write!(f, "invalid config file: {}", error),
+            ConfigFileError::UnknownFormat => write!(f, "config file must be a .toml or .json file"),
+        }
+    }
+}
+
+impl Error for ConfigFileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigFileError::Io(error) => Some(error),
+            ConfigFileError::Toml(error) => Some(error),
+            ConfigFileError::Json(error) => Some(error),
+            ConfigFileError::UnknownFormat => None,
+        }
+    }
+}
@@ -1,10 +1,29 @@
-use std::time::Duration;
+use std::any::Any;
+use std::time::{Duration, Instant};
+use winit::dpi::Position;
 use winit::event_loop::EventLoopWindowTarget;
 use winit::window::{WindowBuilder, WindowId};
+use crate::animation::animatable::Animatable;
+use crate::animation::animator::Animation;
+use crate::animation::tween::Tween;
 use crate::application::{Application, TimerId};
+use crate::clipboard::Clipboard;
+use crate::clock::Clock;
+use crate::context::window_context::FrameCallback;
+use crate::event::input::PointerId;
+use crate::event::recording::{InputRecording, ReplaySpeed};
+use crate::event::synthetic::SyntheticEvent;
+use crate::event::window_message::WindowMessage;
+#[cfg(feature = "gamepad")]
+use crate::gamepad::{Gamepad, GamepadBackend};
+use crate::monitor::{Monitor, MonitorPlacement};
 use crate::custom_event::CustomEvent;
+use crate::shortcut::{CommandRegistry, ShortcutConflict};
 use crate::ui::UI;
-use crate::window::Window;
+use crate::window::{owned_window_builder, popup_window_builder, Window};
+use crate::window_config::{GraphicsSettings, WindowConfig};
+use crate::window_control::WindowControl;
+use crate::window_state::{WindowGeometry, WindowStateManager};
 
 pub struct Context<'a> {
     application: &'a mut Application,
@@ -19,6 +38,19 @@ impl<'a> Context<'a> {
         }
     }
 
+    pub fn now(&self) -> Instant {
+        self.application.now()
+    }
+
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.application.set_clock(clock);
+    }
+
+    /// See `Application::tick`.
+    pub fn tick(&mut self) {
+        self.application.tick(self.event_loop);
+    }
+
     pub fn set_timer(&mut self, time: Duration, f: impl FnOnce(TimerId, &mut Application , &EventLoopWindowTarget<CustomEvent>) + 'static) -> TimerId {
         self.application.add_timer(time, Box::new(f))
     }
@@ -35,21 +67,288 @@ impl<'a> Context<'a> {
         self.application.remove_interval(timer_id);
     }
 
+    pub(crate) fn add_animation_frame(&mut self, window_id: WindowId, callback: FrameCallback) -> TimerId {
+        self.application.add_animation_frame(window_id, callback)
+    }
+
+    pub(crate) fn remove_animation_frame(&mut self, window_id: WindowId, frame_id: TimerId) {
+        self.application.remove_animation_frame(window_id, frame_id);
+    }
+
+    pub(crate) fn add_animation<T: Animatable + 'static>(&mut self, window_id: WindowId, tween: Tween<T>) -> Animation<T> {
+        self.application.add_animation(window_id, tween)
+    }
+
     pub fn new_window(&mut self, wb: WindowBuilder, ui: impl UI + 'static) -> Option<&mut Window> {
-        let mut window = Window::new(wb, self.event_loop, ui);
+        self.open_window(None, wb, GraphicsSettings::default(), Box::new(ui))
+    }
+
+    /// Opens a window described by a `WindowConfig`, named if the config has a name.
+    pub fn new_window_from_config(&mut self, config: &WindowConfig, ui: Box<dyn UI>) -> Option<&mut Window> {
+        self.open_window(config.name.as_deref(), config.window_builder(), config.graphics, ui)
+    }
+
+    fn open_window(&mut self, name: Option<&str>, wb: WindowBuilder, graphics: GraphicsSettings, ui: Box<dyn UI>) -> Option<&mut Window> {
+        let wb = match name {
+            Some(name) => self.application.restore_window_state(name, wb, self.event_loop),
+            None => wb,
+        };
+        let mut window = Window::create(wb, graphics, self.event_loop, ui, self.application.now());
         let window_id = window.id();
+        if let Some(name) = name {
+            self.application.set_window_name(window_id, name);
+        }
         window.on_created(self.application, self.event_loop);
         self.application.add_window(window_id, window)
     }
 
+    pub fn available_monitors(&self) -> Vec<Monitor> {
+        let primary = self.event_loop.primary_monitor();
+        self.event_loop.available_monitors()
+            .map(|handle| {
+                let is_primary = primary.as_ref() == Some(&handle);
+                Monitor::new(handle, is_primary)
+            })
+            .collect()
+    }
+
+    pub fn primary_monitor(&self) -> Option<Monitor> {
+        self.event_loop.primary_monitor().map(|handle| Monitor::new(handle, true))
+    }
+
+    pub fn new_window_on_monitor(&mut self, monitor: &Monitor, placement: MonitorPlacement, wb: WindowBuilder, ui: impl UI + 'static) -> Option<&mut Window> {
+        self.new_window(monitor.place(placement, wb), ui)
+    }
+
+    /// Opens a window with a stable name, restoring its last saved geometry when a `WindowStateManager` is set.
+    pub fn new_named_window(&mut self, name: &str, wb: WindowBuilder, ui: impl UI + 'static) -> Option<&mut Window> {
+        self.open_window(Some(name), wb, GraphicsSettings::default(), Box::new(ui))
+    }
+
+    pub fn set_window_state_manager(&mut self, manager: WindowStateManager) {
+        self.application.set_window_state_manager(manager);
+    }
+
+    /// Opens a window owned by `owner_id`, such as a tool palette. It closes together with its owner.
+    /// Only on Windows does the system also keep it above its owner and minimize it with it.
+    /// From the owner's own event handler, use `WindowContext::open_owned_window`.
+    pub fn new_owned_window(&mut self, owner_id: WindowId, wb: WindowBuilder, ui: impl UI + 'static) -> Option<&mut Window> {
+        let wb = owned_window_builder(self.application.get_mut_window(owner_id)?.winit_window(), wb);
+        self.new_child_window(owner_id, wb, ui)
+    }
+
+    /// Opens a modal dialog on `owner_id`. Input to the owner is blocked until the dialog closes,
+    /// then the owner receives `UIEvent::ModalClose` with the dialog's result.
+    /// Returns `None` if the owner doesn't exist or already has a modal window.
+    /// From the owner's own event handler, use `WindowContext::open_modal_window`.
+    pub fn new_modal_window(&mut self, owner_id: WindowId, wb: WindowBuilder, ui: impl UI + 'static) -> Option<&mut Window> {
+        let wb = owned_window_builder(self.application.get_mut_window(owner_id)?.winit_window(), wb);
+        self.new_modal_child_window(owner_id, wb, ui)
+    }
+
+    /// Opens an undecorated popup, such as a dropdown, tooltip or context menu, owned by `parent_id`
+    /// at `position`, relative to the parent's client area. It tries not to take activation from the parent,
+    /// and is dismissed on a click outside of it, on Escape, or when the application loses focus.
+    /// From the parent's own event handler, use `WindowContext::open_popup_window`.
+    pub fn new_popup_window(&mut self, parent_id: WindowId, position: impl Into<Position>, wb: WindowBuilder, ui: impl UI + 'static) -> Option<&mut Window> {
+        let wb = popup_window_builder(self.application.get_mut_window(parent_id)?.winit_window(), position.into(), wb);
+        self.new_popup_child_window(parent_id, wb, ui)
+    }
+
+    pub fn dismiss_popups(&mut self) {
+        self.application.dismiss_popups();
+    }
+
+    /// Opens a window built from `wb` as a child of `parent_id`, which may be out of the window map
+    /// while it handles an event.
+    pub(crate) fn new_child_window(&mut self, parent_id: WindowId, wb: WindowBuilder, ui: impl UI + 'static) -> Option<&mut Window> {
+        let mut window = Window::create(wb, GraphicsSettings::default(), self.event_loop, Box::new(ui), self.application.now());
+        let window_id = window.id();
+        self.application.set_parent_window(window_id, parent_id);
+        window.on_created(self.application, self.event_loop);
+        self.application.add_window(window_id, window)
+    }
+
+    pub(crate) fn new_modal_child_window(&mut self, owner_id: WindowId, wb: WindowBuilder, ui: impl UI + 'static) -> Option<&mut Window> {
+        if self.application.modal_window(owner_id).is_some() {
+            return None;
+        }
+        let window_id = self.new_child_window(owner_id, wb, ui)?.id();
+        self.application.set_modal_window(owner_id, window_id);
+        self.application.get_mut_window(window_id)
+    }
+
+    pub(crate) fn new_popup_child_window(&mut self, parent_id: WindowId, wb: WindowBuilder, ui: impl UI + 'static) -> Option<&mut Window> {
+        let window_id = self.new_child_window(parent_id, wb, ui)?.id();
+        self.application.add_popup_window(window_id);
+        self.application.get_mut_window(window_id)
+    }
+
+    pub fn parent_window(&self, window_id: WindowId) -> Option<WindowId> {
+        self.application.parent_window(window_id)
+    }
+
+    pub fn child_windows(&self, window_id: WindowId) -> Vec<WindowId> {
+        self.application.child_windows(window_id)
+    }
+
+    pub(crate) fn set_modal_result(&mut self, window_id: WindowId, result: Box<dyn Any>) {
+        self.application.set_modal_result(window_id, result);
+    }
+
+    pub fn set_window_name(&mut self, window_id: WindowId, name: impl Into<String>) {
+        self.application.set_window_name(window_id, name);
+    }
+
+    pub fn find_window(&self, name: &str) -> Option<WindowId> {
+        self.application.find_window(name)
+    }
+
+    /// Delivers `message` to the window as `UIEvent::Message` on the next event loop iteration.
+    pub fn send_message(&mut self, window_id: WindowId, message: impl Any) {
+        self.application.post_message(window_id, WindowMessage::new(None, message));
+    }
+
+    /// Like `send_message`, addressing the window by the name given with `set_window_name`.
+    /// Returns `false` if no window has that name.
+    pub fn send_message_to(&mut self, name: &str, message: impl Any) -> bool {
+        match self.find_window(name) {
+            Some(window_id) => {
+                self.send_message(window_id, message);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn broadcast_message(&mut self, message: impl Any) {
+        self.application.broadcast_message(WindowMessage::new(None, message));
+    }
+
+    pub(crate) fn save_fullscreen_geometry(&mut self, window_id: WindowId, geometry: WindowGeometry) {
+        self.application.save_fullscreen_geometry(window_id, geometry);
+    }
+
+    pub(crate) fn set_pointer_capture(&mut self, window_id: WindowId, pointer_id: PointerId) {
+        self.application.set_pointer_capture(window_id, pointer_id);
+    }
+
+    pub(crate) fn release_pointer_capture(&mut self, window_id: WindowId, pointer_id: PointerId) {
+        self.application.release_pointer_capture(window_id, pointer_id);
+    }
+
+    pub(crate) fn has_pointer_capture(&self, window_id: WindowId, pointer_id: PointerId) -> bool {
+        self.application.pointer_captures(window_id).contains(&pointer_id)
+    }
+
+    pub(crate) fn post_message(&mut self, window_id: WindowId, message: WindowMessage) {
+        self.application.post_message(window_id, message);
+    }
+
+    pub(crate) fn post_broadcast_message(&mut self, message: WindowMessage) {
+        self.application.broadcast_message(message);
+    }
+
     pub fn get_mut_window(&mut self, window_id: WindowId) -> Option<&mut Window> {
         self.application.get_mut_window(window_id)
     }
 
+    /// Records the input delivered to the window until `stop_recording`, except replayed input.
+    pub fn start_recording(&mut self, window_id: WindowId) {
+        self.application.start_recording(window_id);
+    }
+
+    pub fn stop_recording(&mut self, window_id: WindowId) -> Option<InputRecording> {
+        self.application.stop_recording(window_id)
+    }
+
+    /// Delivers the recorded input to the window, in place of the UI it was recorded with if needed.
+    /// To replay without showing anything, open the window with `WindowBuilder::with_visible(false)`.
+    pub fn replay(&mut self, window_id: WindowId, recording: InputRecording, speed: ReplaySpeed) {
+        self.application.replay(window_id, recording, speed);
+    }
+
+    /// Delivers the event to the window on the next event loop iteration, as if it came from the system,
+    /// see `SyntheticEvent` for how it differs. Meant for tests and automation:
+    ///
+    /// ```no_run
+    /// use skia_desktop::context::context::Context;
+    /// use skia_desktop::event::input::PointerButton;
+    /// use skia_desktop::event::synthetic::SyntheticEvent;
+    /// use skia_desktop::winit::window::WindowId;
+    ///
+    /// fn resize_and_click(context: &mut Context, window_id: WindowId) {
+    ///     context.inject_event(window_id, SyntheticEvent::resize(800.0, 600.0));
+    ///     context.inject_events(window_id, SyntheticEvent::click(10.0, 20.0, PointerButton::Primary));
+    /// }
+    /// ```
+    pub fn inject_event(&mut self, window_id: WindowId, event: impl Into<SyntheticEvent>) {
+        self.application.inject_event(window_id, event);
+    }
+
+    /// Delivers the events in order, see `inject_event`.
+    pub fn inject_events(&mut self, window_id: WindowId, events: impl IntoIterator<Item = impl Into<SyntheticEvent>>) {
+        for event in events {
+            self.application.inject_event(window_id, event);
+        }
+    }
+
+    /// Replaces the backend gamepads are read from, e.g. with a `MockGamepadBackend` in tests.
+    #[cfg(feature = "gamepad")]
+    pub fn set_gamepad_backend(&mut self, backend: impl GamepadBackend + 'static) {
+        self.application.set_gamepad_backend(backend);
+    }
+
+    /// Gamepads connected at the moment. Their input arrives as `UIEvent::Gamepad` in the focused window.
+    #[cfg(feature = "gamepad")]
+    pub fn gamepads(&self) -> &[Gamepad] {
+        self.application.gamepads()
+    }
+
+    pub fn clipboard(&mut self) -> &mut Clipboard {
+        self.application.clipboard()
+    }
+
+    /// Commands triggered by their shortcuts in every window.
+    pub fn commands(&mut self) -> &mut CommandRegistry {
+        self.application.commands()
+    }
+
+    pub(crate) fn window_commands(&mut self, window_id: WindowId) -> &mut CommandRegistry {
+        self.application.window_commands(window_id)
+    }
+
+    /// See `WindowContext::shortcut_conflicts`.
+    pub fn shortcut_conflicts(&self, window_id: WindowId) -> Vec<ShortcutConflict> {
+        self.application.shortcut_conflicts(window_id)
+    }
+
+    pub fn focused_window(&self) -> Option<WindowId> {
+        self.application.focused_window()
+    }
+
+    /// Open windows in the order they last had focus, the most recent one last.
+    pub fn focus_history(&self) -> &[WindowId] {
+        self.application.focus_history()
+    }
+
+    /// Asks the platform to focus the window. Returns `false` if there is no such window.
+    pub fn focus_window(&mut self, window_id: WindowId) -> bool {
+        match self.application.get_mut_window(window_id) {
+            Some(window) => {
+                window.focus_window();
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn close_window(&mut self, window_id: WindowId) {
         self.application.request_close_window(window_id);
     }
 
+    /// Closes the windows still open, which receive `UIEvent::WindowClose`, then stops the event loop.
+    /// The exit requested handler can cancel it while a window is open, see `EventHandler::add_exit_requested_handler`.
     pub fn exit(&mut self) {
         self.application.request_exit();
     }
@@ -1,2 +1,3 @@
+#[allow(clippy::module_inception)]
 pub mod context;
 pub mod window_context;
\ No newline at end of file
@@ -1,11 +1,32 @@
-use std::time::Duration;
-use winit::window::{Window};
+use std::any::Any;
+use std::time::{Duration, Instant};
+use winit::dpi::Position;
+use winit::error::ExternalError;
+use winit::monitor::VideoMode;
+use winit::window::{Fullscreen, Window, WindowBuilder, WindowId};
+use crate::animation::animatable::Animatable;
+use crate::animation::animator::Animation;
+use crate::animation::tween::Tween;
 use crate::application::{TimerId};
 use crate::context::context::Context;
+use crate::event::input::PointerId;
+use crate::event::recording::InputRecording;
+use crate::event::synthetic::SyntheticEvent;
+use crate::event::window_message::WindowMessage;
+use crate::monitor::Monitor;
+use crate::shortcut::{CommandRegistry, ShortcutConflict};
+use crate::ui::UI;
+use crate::window::{owned_window_builder, popup_window_builder};
+use crate::window_control::WindowControl;
+use crate::window_state::WindowGeometry;
+
+pub(crate) type FrameCallback = Box<dyn FnOnce(&mut WindowContext, Instant)>;
 
 pub struct WindowContext<'a> {
     application_context: Context<'a>,
     winit_window: &'a mut Window,
+    close_prevented: bool,
+    drop_accepted: bool,
 }
 
 impl<'a> WindowContext<'a> {
@@ -13,6 +34,8 @@ impl<'a> WindowContext<'a> {
         Self {
             application_context,
             winit_window,
+            close_prevented: false,
+            drop_accepted: false,
         }
     }
 
@@ -20,14 +43,163 @@ impl<'a> WindowContext<'a> {
         self.winit_window.request_redraw();
     }
 
+    /// Moves the window with the cursor, ignored where the platform can't. See `try_start_drag`.
     pub fn start_drag(&mut self) {
-        self.winit_window.drag_window().unwrap();
+        let _ = self.try_start_drag();
+    }
+
+    /// Like `start_drag`, reporting why the window couldn't be dragged.
+    pub fn try_start_drag(&mut self) -> Result<(), ExternalError> {
+        self.drag_window()
     }
 
     pub fn close_window(&mut self) {
         self.application_context.close_window(self.winit_window.id());
     }
 
+    /// Closes this modal window, handing `result` to its owner in `UIEvent::ModalClose`.
+    pub fn close_with_result(&mut self, result: impl Any) {
+        self.application_context.set_modal_result(self.winit_window.id(), Box::new(result));
+        self.close_window();
+    }
+
+    /// Like `Context::new_owned_window` with this window as the owner.
+    pub fn open_owned_window(&mut self, wb: WindowBuilder, ui: impl UI + 'static) -> Option<&mut crate::window::Window> {
+        let wb = owned_window_builder(self.winit_window, wb);
+        self.application_context.new_child_window(self.winit_window.id(), wb, ui)
+    }
+
+    /// Like `Context::new_modal_window` with this window as the owner.
+    pub fn open_modal_window(&mut self, wb: WindowBuilder, ui: impl UI + 'static) -> Option<&mut crate::window::Window> {
+        let wb = owned_window_builder(self.winit_window, wb);
+        self.application_context.new_modal_child_window(self.winit_window.id(), wb, ui)
+    }
+
+    /// Like `Context::new_popup_window` with this window as the parent.
+    pub fn open_popup_window(&mut self, position: impl Into<Position>, wb: WindowBuilder, ui: impl UI + 'static) -> Option<&mut crate::window::Window> {
+        let wb = popup_window_builder(self.winit_window, position.into(), wb);
+        self.application_context.new_popup_child_window(self.winit_window.id(), wb, ui)
+    }
+
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.application_context.set_window_name(self.winit_window.id(), name);
+    }
+
+    /// Sends `message` to another window, with this window as the sender.
+    pub fn send_message(&mut self, window_id: WindowId, message: impl Any) {
+        let message = WindowMessage::new(Some(self.winit_window.id()), message);
+        self.application_context.post_message(window_id, message);
+    }
+
+    /// Like `send_message`, addressing the window by name. Returns `false` if no window has that name.
+    pub fn send_message_to(&mut self, name: &str, message: impl Any) -> bool {
+        match self.application_context.find_window(name) {
+            Some(window_id) => {
+                self.send_message(window_id, message);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sends `message` to every other window.
+    pub fn broadcast_message(&mut self, message: impl Any) {
+        let message = WindowMessage::new(Some(self.winit_window.id()), message);
+        self.application_context.post_broadcast_message(message);
+    }
+
+    /// Covers `monitor`, or the current monitor if `None`, without changing its video mode.
+    pub fn enter_borderless_fullscreen(&mut self, monitor: Option<&Monitor>) {
+        self.save_fullscreen_geometry();
+        self.set_fullscreen(Some(Fullscreen::Borderless(monitor.map(|monitor| monitor.handle().clone()))));
+    }
+
+    /// Switches the monitor of `video_mode`, see `Monitor::video_modes`, to that mode and covers it.
+    pub fn enter_exclusive_fullscreen(&mut self, video_mode: VideoMode) {
+        self.save_fullscreen_geometry();
+        self.set_fullscreen(Some(Fullscreen::Exclusive(video_mode)));
+    }
+
+    /// Leaves fullscreen. The geometry the window had before entering it is restored on the `Resized`
+    /// that follows, once the window has actually left fullscreen.
+    pub fn exit_fullscreen(&mut self) {
+        self.set_fullscreen(None);
+    }
+
+    /// Keeps reporting `pointer_id` to this window while it is dragged outside of it, until every
+    /// button is released or the pointer is cancelled. Over the application's other windows, its input
+    /// goes to this window instead of them; elsewhere platforms only report it while a button is held.
+    pub fn set_pointer_capture(&mut self, pointer_id: PointerId) {
+        self.application_context.set_pointer_capture(self.winit_window.id(), pointer_id);
+    }
+
+    pub fn release_pointer_capture(&mut self, pointer_id: PointerId) {
+        self.application_context.release_pointer_capture(self.winit_window.id(), pointer_id);
+    }
+
+    pub fn has_pointer_capture(&self, pointer_id: PointerId) -> bool {
+        self.application_context.has_pointer_capture(self.winit_window.id(), pointer_id)
+    }
+
+    /// Records the input delivered to this window until `stop_recording`, except replayed input, see `Context::replay`.
+    pub fn start_recording(&mut self) {
+        self.application_context.start_recording(self.winit_window.id());
+    }
+
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        self.application_context.stop_recording(self.winit_window.id())
+    }
+
+    /// Delivers the event to this window on the next event loop iteration, see `Context::inject_event`.
+    pub fn inject_event(&mut self, event: impl Into<SyntheticEvent>) {
+        self.application_context.inject_event(self.winit_window.id(), event);
+    }
+
+    pub fn inject_events(&mut self, events: impl IntoIterator<Item = impl Into<SyntheticEvent>>) {
+        self.application_context.inject_events(self.winit_window.id(), events);
+    }
+
+    /// Commands triggered by their shortcuts only while this window has the keyboard focus.
+    pub fn commands(&mut self) -> &mut CommandRegistry {
+        self.application_context.window_commands(self.winit_window.id())
+    }
+
+    /// Conflicts among the application's commands, among this window's, and between both levels, where
+    /// this window's binding shadows the application's.
+    pub fn shortcut_conflicts(&self) -> Vec<ShortcutConflict> {
+        self.application_context.shortcut_conflicts(self.winit_window.id())
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.fullscreen().is_some()
+    }
+
+    fn save_fullscreen_geometry(&mut self) {
+        // Switching between fullscreen modes keeps the geometry from before the first one.
+        if !self.is_fullscreen() {
+            let geometry = WindowGeometry::capture(self.winit_window);
+            self.application_context.save_fullscreen_geometry(self.winit_window.id(), geometry);
+        }
+    }
+
+    /// Keeps the window open while handling `UIEvent::CloseRequested`.
+    pub fn prevent_close(&mut self) {
+        self.close_prevented = true;
+    }
+
+    pub(crate) fn is_close_prevented(&self) -> bool {
+        self.close_prevented
+    }
+
+    /// Call it on `UIEvent::FileDragEnter` or `UIEvent::FileDragOver` to receive the files if they are dropped there.
+    pub fn accept_drop(&mut self) {
+        self.drop_accepted = true;
+    }
+
+    pub(crate) fn is_drop_accepted(&self) -> bool {
+        self.drop_accepted
+    }
+
     pub fn application(&mut self) -> &mut Context<'a> {
         &mut self.application_context
     }
@@ -61,4 +233,32 @@ impl<'a> WindowContext<'a> {
     pub fn remove_interval(&mut self, id: TimerId) {
         self.application_context.clear_interval(id);
     }
-}
\ No newline at end of file
+
+    /// Runs `callback` right before the next draw of this window, with the frame timestamp shared by
+    /// every callback of that frame. Frames are only produced while callbacks are pending, so request
+    /// again from the callback to keep animating. The callback can't reach the UI itself, share state
+    /// with it through an `Rc<RefCell<_>>`.
+    pub fn request_animation_frame(&mut self, callback: impl FnOnce(&mut WindowContext, Instant) + 'static) -> TimerId {
+        let id = self.application_context.add_animation_frame(self.winit_window.id(), Box::new(callback));
+        self.winit_window.request_redraw();
+        id
+    }
+
+    pub fn cancel_animation_frame(&mut self, id: TimerId) {
+        self.application_context.remove_animation_frame(self.winit_window.id(), id);
+    }
+
+    /// Starts a tween on this window. The window keeps redrawing while any of its animations is running,
+    /// and the UI receives `UIEvent::AnimationEnd` when this one completes, followed by its `Animation::on_end` callback.
+    pub fn animate<T: Animatable + 'static>(&mut self, tween: Tween<T>) -> Animation<T> {
+        let animation = self.application_context.add_animation(self.winit_window.id(), tween);
+        self.winit_window.request_redraw();
+        animation
+    }
+}
+
+impl<'a> WindowControl for WindowContext<'a> {
+    fn winit_window(&self) -> &Window {
+        self.winit_window
+    }
+}
@@ -1,7 +1,12 @@
 use winit::window::WindowId;
+use crate::single_instance::InstanceArgs;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum CustomEvent{
     CloseWindow(WindowId),
+    SecondInstance(InstanceArgs),
+    /// Sent by the gamepad backend when it has events, the loop polls it on every iteration.
+    #[cfg(feature = "gamepad")]
+    GamepadInput,
     Exit
 }
\ No newline at end of file
@@ -0,0 +1,770 @@
+use std::f32::consts::PI;
+use std::time::{Duration, Instant};
+use skia_safe::Point;
+use winit::event::TouchPhase;
+use crate::event::input::{PointerButton, PointerEvent, PointerId, PointerType};
+use crate::event::input_event::InputEvent;
+use crate::event::ui_event::UIEvent;
+
+/// Farthest a pointer can move before a press turns into a pan, in logical pixels.
+const MOUSE_SLOP: f32 = 4.0;
+const TOUCH_SLOP: f32 = 10.0;
+pub(crate) const LONG_PRESS_TIME: Duration = Duration::from_millis(500);
+/// Slowest release speed that flings, in logical pixels per second.
+const FLING_MIN_VELOCITY: f32 = 250.0;
+/// A pan held still this long before being released doesn't fling.
+const FLING_MAX_REST: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GesturePhase {
+    Began,
+    Changed,
+    Ended,
+    Cancelled,
+}
+
+impl From<TouchPhase> for GesturePhase {
+    fn from(phase: TouchPhase) -> Self {
+        match phase {
+            TouchPhase::Started => GesturePhase::Began,
+            TouchPhase::Moved => GesturePhase::Changed,
+            TouchPhase::Ended => GesturePhase::Ended,
+            TouchPhase::Cancelled => GesturePhase::Cancelled,
+        }
+    }
+}
+
+/// Recognized from pointer events and touchpad gestures, delivered after the pointer events they come from.
+/// Positions are in logical pixels and velocities are per second.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GestureEvent {
+    /// A press released without moving. `count` is `2` for a double tap.
+    Tap {
+        position: Point,
+        count: u32,
+    },
+    /// Begins once a pointer is held still long enough, changes as it moves afterwards and ends on release.
+    LongPress {
+        phase: GesturePhase,
+        position: Point,
+    },
+    /// A single pointer dragged with the primary button or a finger. `translation` is the distance
+    /// since the pan began and `delta` since the previous event.
+    Pan {
+        phase: GesturePhase,
+        position: Point,
+        translation: Point,
+        delta: Point,
+        velocity: Point,
+    },
+    /// Two fingers on a touchscreen or a touchpad. `scale` is relative to the start of the gesture
+    /// and `scale_delta` to the previous event.
+    Pinch {
+        phase: GesturePhase,
+        center: Point,
+        scale: f32,
+        scale_delta: f32,
+        velocity: f32,
+    },
+    /// Two fingers on a touchscreen or a touchpad. Angles are in radians, clockwise positive.
+    Rotate {
+        phase: GesturePhase,
+        center: Point,
+        rotation: f32,
+        rotation_delta: f32,
+        velocity: f32,
+    },
+    /// A pan was released while moving fast, sent right after it ended. Use it to start inertial scrolling.
+    Fling {
+        position: Point,
+        velocity: Point,
+    },
+    /// Double tap with two fingers on a macOS touchpad, usually zooms to the content under the cursor.
+    SmartMagnify {
+        position: Point,
+    },
+}
+
+struct TrackedPointer {
+    id: PointerId,
+    pointer_type: PointerType,
+    start: Point,
+    position: Point,
+}
+
+struct Pan {
+    start: Point,
+    position: Point,
+    time: Instant,
+    velocity: Point,
+    active: bool,
+}
+
+struct LongPress {
+    deadline: Instant,
+    fired: bool,
+}
+
+struct TwoFinger {
+    center: Point,
+    distance: f32,
+    angle: f32,
+    scale: f32,
+    rotation: f32,
+    time: Instant,
+    scale_velocity: f32,
+    rotation_velocity: f32,
+}
+
+/// A touchpad pinch or rotation in progress.
+struct Motion {
+    total: f32,
+    velocity: f32,
+    time: Instant,
+}
+
+impl Motion {
+    fn new(total: f32, now: Instant) -> Self {
+        Self {
+            total,
+            velocity: 0.0,
+            time: now,
+        }
+    }
+
+    fn advance(&mut self, total: f32, now: Instant) {
+        self.velocity = smooth(self.velocity, total - self.total, now.saturating_duration_since(self.time));
+        self.total = total;
+        self.time = now;
+    }
+}
+
+/// Follows the pointers of one window and turns them into `GestureEvent`s.
+#[derive(Default)]
+pub(crate) struct GestureRecognizer {
+    pointers: Vec<TrackedPointer>,
+    pan: Option<Pan>,
+    long_press: Option<LongPress>,
+    long_press_requested: bool,
+    tap_possible: bool,
+    two_finger: Option<TwoFinger>,
+    touchpad_pinch: Option<Motion>,
+    touchpad_rotate: Option<Motion>,
+    hover_position: Point,
+}
+
+impl GestureRecognizer {
+    pub fn process(&mut self, event: &UIEvent, now: Instant) -> Vec<GestureEvent> {
+        match event {
+            UIEvent::PointerDown(pointer) => self.pointer_down(pointer, now),
+            UIEvent::PointerMove(pointer) => {
+                if pointer.pointer_type == PointerType::Mouse {
+                    self.hover_position = pointer.position;
+                }
+                self.pointer_move(pointer, now)
+            }
+            UIEvent::PointerUp(pointer) => self.pointer_up(pointer, now, false),
+            UIEvent::PointerCancel(pointer) => self.pointer_up(pointer, now, true),
+            _ => vec![],
+        }
+    }
+
+    /// Touchpad gestures arrive as input of their own, at the cursor position.
+    pub fn process_touchpad(&mut self, event: &InputEvent, now: Instant) -> Vec<GestureEvent> {
+        let center = self.hover_position;
+        match event {
+            InputEvent::TouchpadMagnify { delta, phase } => {
+                let phase = GesturePhase::from(*phase);
+                let motion = match phase {
+                    GesturePhase::Began => self.touchpad_pinch.insert(Motion::new(1.0, now)),
+                    _ => self.touchpad_pinch.get_or_insert_with(|| Motion::new(1.0, now)),
+                };
+                let scale_delta = 1.0 + *delta as f32;
+                motion.advance(motion.total * scale_delta, now);
+                let event = GestureEvent::Pinch {
+                    phase,
+                    center,
+                    scale: motion.total,
+                    scale_delta,
+                    velocity: motion.velocity,
+                };
+                if matches!(phase, GesturePhase::Ended | GesturePhase::Cancelled) {
+                    self.touchpad_pinch = None;
+                }
+                vec![event]
+            }
+            InputEvent::TouchpadRotate { delta, phase } => {
+                let phase = GesturePhase::from(*phase);
+                let motion = match phase {
+                    GesturePhase::Began => self.touchpad_rotate.insert(Motion::new(0.0, now)),
+                    _ => self.touchpad_rotate.get_or_insert_with(|| Motion::new(0.0, now)),
+                };
+                // winit reports degrees, counterclockwise positive.
+                let rotation_delta = -delta.to_radians();
+                motion.advance(motion.total + rotation_delta, now);
+                let event = GestureEvent::Rotate {
+                    phase,
+                    center,
+                    rotation: motion.total,
+                    rotation_delta,
+                    velocity: motion.velocity,
+                };
+                if matches!(phase, GesturePhase::Ended | GesturePhase::Cancelled) {
+                    self.touchpad_rotate = None;
+                }
+                vec![event]
+            }
+            InputEvent::SmartMagnify => vec![GestureEvent::SmartMagnify { position: center }],
+            _ => vec![],
+        }
+    }
+
+    /// Whether a long press started since the last call, the window then checks back after `LONG_PRESS_TIME`.
+    pub fn take_long_press_request(&mut self) -> bool {
+        std::mem::take(&mut self.long_press_requested)
+    }
+
+    pub fn check_long_press(&mut self, now: Instant) -> Option<GestureEvent> {
+        let long_press = self.long_press.as_mut()?;
+        let panning = self.pan.as_ref().is_some_and(|pan| pan.active);
+        if long_press.fired || now < long_press.deadline || panning || self.pointers.len() != 1 {
+            return None;
+        }
+        long_press.fired = true;
+        self.tap_possible = false;
+        Some(GestureEvent::LongPress {
+            phase: GesturePhase::Began,
+            position: self.pointers[0].position,
+        })
+    }
+
+    fn pointer_down(&mut self, pointer: &PointerEvent, now: Instant) -> Vec<GestureEvent> {
+        if pointer.pointer_type == PointerType::Mouse && pointer.button != Some(PointerButton::Primary) {
+            return vec![];
+        }
+        if self.pointers.iter().any(|tracked| tracked.id == pointer.pointer_id) {
+            return vec![];
+        }
+        self.pointers.push(TrackedPointer {
+            id: pointer.pointer_id,
+            pointer_type: pointer.pointer_type,
+            start: pointer.position,
+            position: pointer.position,
+        });
+        match self.pointers.len() {
+            1 => {
+                self.pan = Some(Pan {
+                    start: pointer.position,
+                    position: pointer.position,
+                    time: now,
+                    velocity: Point::default(),
+                    active: false,
+                });
+                self.long_press = Some(LongPress {
+                    deadline: now + LONG_PRESS_TIME,
+                    fired: false,
+                });
+                self.long_press_requested = true;
+                self.tap_possible = true;
+                vec![]
+            }
+            2 => {
+                // A second finger turns the single pointer gestures into a pinch and rotation.
+                let mut events = self.end_single_pointer(pointer.position, now, GesturePhase::Ended, false);
+                let (center, distance, angle) = self.two_finger_geometry();
+                self.two_finger = Some(TwoFinger {
+                    center,
+                    distance,
+                    angle,
+                    scale: 1.0,
+                    rotation: 0.0,
+                    time: now,
+                    scale_velocity: 0.0,
+                    rotation_velocity: 0.0,
+                });
+                events.extend(self.two_finger_events(GesturePhase::Began, 1.0, 0.0));
+                events
+            }
+            _ => vec![],
+        }
+    }
+
+    fn pointer_move(&mut self, pointer: &PointerEvent, now: Instant) -> Vec<GestureEvent> {
+        let Some(index) = self.pointers.iter().position(|tracked| tracked.id == pointer.pointer_id) else {
+            return vec![];
+        };
+        self.pointers[index].position = pointer.position;
+
+        if self.two_finger.is_some() {
+            if index > 1 {
+                return vec![];
+            }
+            let (center, distance, angle) = self.two_finger_geometry();
+            let Some(two_finger) = &mut self.two_finger else {
+                return vec![];
+            };
+            let scale_delta = if two_finger.distance > 0.0 { distance / two_finger.distance } else { 1.0 };
+            let rotation_delta = normalize_angle(angle - two_finger.angle);
+            let elapsed = now.saturating_duration_since(two_finger.time);
+            let scale = two_finger.scale * scale_delta;
+            two_finger.scale_velocity = smooth(two_finger.scale_velocity, scale - two_finger.scale, elapsed);
+            two_finger.rotation_velocity = smooth(two_finger.rotation_velocity, rotation_delta, elapsed);
+            two_finger.scale = scale;
+            two_finger.rotation += rotation_delta;
+            two_finger.center = center;
+            two_finger.distance = distance;
+            two_finger.angle = angle;
+            two_finger.time = now;
+            return self.two_finger_events(GesturePhase::Changed, scale_delta, rotation_delta);
+        }
+
+        if self.pointers.len() != 1 {
+            return vec![];
+        }
+        let tracked = &self.pointers[0];
+        let slop = if tracked.pointer_type == PointerType::Mouse { MOUSE_SLOP } else { TOUCH_SLOP };
+        let moved = distance(tracked.start, pointer.position) > slop;
+        if let Some(long_press) = &self.long_press {
+            if long_press.fired {
+                return vec![GestureEvent::LongPress {
+                    phase: GesturePhase::Changed,
+                    position: pointer.position,
+                }];
+            }
+            if moved {
+                self.long_press = None;
+            }
+        }
+        let Some(pan) = &mut self.pan else {
+            return vec![];
+        };
+        let delta = pointer.position - pan.position;
+        let elapsed = now.saturating_duration_since(pan.time);
+        pan.velocity = Point::new(smooth(pan.velocity.x, delta.x, elapsed), smooth(pan.velocity.y, delta.y, elapsed));
+        pan.position = pointer.position;
+        pan.time = now;
+        let phase = match (pan.active, moved) {
+            (true, _) => GesturePhase::Changed,
+            (false, true) => GesturePhase::Began,
+            (false, false) => return vec![],
+        };
+        pan.active = true;
+        self.tap_possible = false;
+        vec![GestureEvent::Pan {
+            phase,
+            position: pan.position,
+            translation: pan.position - pan.start,
+            delta: match phase {
+                GesturePhase::Began => pan.position - pan.start,
+                _ => delta,
+            },
+            velocity: pan.velocity,
+        }]
+    }
+
+    fn pointer_up(&mut self, pointer: &PointerEvent, now: Instant, cancelled: bool) -> Vec<GestureEvent> {
+        if pointer.pointer_type == PointerType::Mouse && pointer.button != Some(PointerButton::Primary) && !cancelled {
+            return vec![];
+        }
+        let Some(index) = self.pointers.iter().position(|tracked| tracked.id == pointer.pointer_id) else {
+            return vec![];
+        };
+        let phase = if cancelled { GesturePhase::Cancelled } else { GesturePhase::Ended };
+
+        if self.two_finger.is_some() {
+            if index > 1 {
+                self.pointers.remove(index);
+                return vec![];
+            }
+            let events = self.two_finger_events(phase, 1.0, 0.0);
+            self.two_finger = None;
+            self.pointers.remove(index);
+            // The finger left on the screen can start a new pan, but no tap or long press.
+            if let [remaining] = self.pointers.as_slice() {
+                self.pan = Some(Pan {
+                    start: remaining.position,
+                    position: remaining.position,
+                    time: now,
+                    velocity: Point::default(),
+                    active: false,
+                });
+            }
+            return events;
+        }
+
+        self.pointers.remove(index);
+        if !self.pointers.is_empty() {
+            return vec![];
+        }
+        let tap = self.tap_possible && !cancelled;
+        let mut events = self.end_single_pointer(pointer.position, now, phase, !cancelled);
+        if tap {
+            events.push(GestureEvent::Tap {
+                position: pointer.position,
+                count: pointer.click_count.max(1),
+            });
+        }
+        events
+    }
+
+    /// Ends the pan and long press of the only pointer.
+    fn end_single_pointer(&mut self, position: Point, now: Instant, phase: GesturePhase, fling: bool) -> Vec<GestureEvent> {
+        let mut events = vec![];
+        self.tap_possible = false;
+        if let Some(long_press) = self.long_press.take() {
+            if long_press.fired {
+                events.push(GestureEvent::LongPress { phase, position });
+            }
+        }
+        if let Some(pan) = self.pan.take() {
+            if pan.active {
+                let velocity = match now.saturating_duration_since(pan.time) > FLING_MAX_REST {
+                    true => Point::default(),
+                    false => pan.velocity,
+                };
+                events.push(GestureEvent::Pan {
+                    phase,
+                    position: pan.position,
+                    translation: pan.position - pan.start,
+                    delta: Point::default(),
+                    velocity,
+                });
+                if fling && velocity.length() >= FLING_MIN_VELOCITY {
+                    events.push(GestureEvent::Fling {
+                        position: pan.position,
+                        velocity,
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    /// Center, distance and angle of the first two pointers.
+    fn two_finger_geometry(&self) -> (Point, f32, f32) {
+        let first = self.pointers[0].position;
+        let second = self.pointers[1].position;
+        let center = Point::new((first.x + second.x) / 2.0, (first.y + second.y) / 2.0);
+        let offset = second - first;
+        (center, distance(first, second), offset.y.atan2(offset.x))
+    }
+
+    fn two_finger_events(&self, phase: GesturePhase, scale_delta: f32, rotation_delta: f32) -> Vec<GestureEvent> {
+        let Some(two_finger) = &self.two_finger else {
+            return vec![];
+        };
+        vec![
+            GestureEvent::Pinch {
+                phase,
+                center: two_finger.center,
+                scale: two_finger.scale,
+                scale_delta,
+                velocity: two_finger.scale_velocity,
+            },
+            GestureEvent::Rotate {
+                phase,
+                center: two_finger.center,
+                rotation: two_finger.rotation,
+                rotation_delta,
+                velocity: two_finger.rotation_velocity,
+            },
+        ]
+    }
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    (b - a).length()
+}
+
+/// Wraps an angle difference into `-PI..=PI`, so crossing the negative x axis isn't a full turn.
+fn normalize_angle(angle: f32) -> f32 {
+    let angle = angle % (2.0 * PI);
+    if angle > PI {
+        angle - 2.0 * PI
+    } else if angle < -PI {
+        angle + 2.0 * PI
+    } else {
+        angle
+    }
+}
+
+/// Blends the speed of the latest change into `velocity`, to smooth out uneven event timing.
+fn smooth(velocity: f32, change: f32, elapsed: Duration) -> f32 {
+    let seconds = elapsed.as_secs_f32();
+    if seconds <= 0.0 {
+        return velocity;
+    }
+    velocity * 0.3 + change / seconds * 0.7
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+    use crate::event::input::{Modifiers, PointerButtons};
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn pointer(pointer_id: PointerId, pointer_type: PointerType, x: f32, y: f32) -> PointerEvent {
+        PointerEvent {
+            pointer_id,
+            pointer_type,
+            position: Point::new(x, y),
+            button: (pointer_type == PointerType::Mouse).then_some(PointerButton::Primary),
+            buttons: PointerButtons::default(),
+            pressure: 0.5,
+            click_count: 1,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    fn mouse(x: f32, y: f32) -> PointerEvent {
+        pointer(PointerId::Mouse, PointerType::Mouse, x, y)
+    }
+
+    fn finger(id: u64, x: f32, y: f32) -> PointerEvent {
+        pointer(PointerId::Touch(id), PointerType::Touch, x, y)
+    }
+
+    fn phases(events: &[GestureEvent]) -> Vec<(&'static str, Option<GesturePhase>)> {
+        events.iter()
+            .map(|event| match event {
+                GestureEvent::Tap { .. } => ("tap", None),
+                GestureEvent::LongPress { phase, .. } => ("long press", Some(*phase)),
+                GestureEvent::Pan { phase, .. } => ("pan", Some(*phase)),
+                GestureEvent::Pinch { phase, .. } => ("pinch", Some(*phase)),
+                GestureEvent::Rotate { phase, .. } => ("rotate", Some(*phase)),
+                GestureEvent::Fling { .. } => ("fling", None),
+                GestureEvent::SmartMagnify { .. } => ("smart magnify", None),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn click_in_place_taps() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        assert!(recognizer.process(&UIEvent::PointerDown(mouse(10.0, 10.0)), now).is_empty());
+        recognizer.process(&UIEvent::PointerMove(mouse(12.0, 10.0)), now + ms(10));
+        let events = recognizer.process(&UIEvent::PointerUp(mouse(12.0, 10.0)), now + ms(20));
+        assert_eq!(events, vec![GestureEvent::Tap { position: Point::new(12.0, 10.0), count: 1 }]);
+    }
+
+    #[test]
+    fn secondary_button_makes_no_gesture() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        let mut press = mouse(0.0, 0.0);
+        press.button = Some(PointerButton::Secondary);
+        recognizer.process(&UIEvent::PointerDown(press.clone()), now);
+        assert!(recognizer.process(&UIEvent::PointerUp(press), now).is_empty());
+    }
+
+    #[test]
+    fn pan_begins_past_the_slop_then_flings() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        recognizer.process(&UIEvent::PointerDown(finger(1, 0.0, 0.0)), now);
+        // Within the touch slop.
+        assert!(recognizer.process(&UIEvent::PointerMove(finger(1, 5.0, 0.0)), now + ms(10)).is_empty());
+
+        let began = recognizer.process(&UIEvent::PointerMove(finger(1, 20.0, 0.0)), now + ms(20));
+        let GestureEvent::Pan { phase, translation, delta, .. } = began[0] else { panic!("{:?}", began) };
+        assert_eq!((phase, translation, delta), (GesturePhase::Began, Point::new(20.0, 0.0), Point::new(20.0, 0.0)));
+
+        let changed = recognizer.process(&UIEvent::PointerMove(finger(1, 40.0, 0.0)), now + ms(30));
+        let GestureEvent::Pan { phase, translation, delta, velocity, .. } = changed[0] else { panic!("{:?}", changed) };
+        assert_eq!((phase, translation, delta), (GesturePhase::Changed, Point::new(40.0, 0.0), Point::new(20.0, 0.0)));
+        // 5, 15 then 20 pixels every 10ms, smoothed.
+        assert!((velocity.x - 1746.5).abs() < 0.5, "{}", velocity.x);
+        assert_eq!(velocity.y, 0.0);
+
+        let ended = recognizer.process(&UIEvent::PointerUp(finger(1, 40.0, 0.0)), now + ms(40));
+        assert_eq!(phases(&ended), vec![("pan", Some(GesturePhase::Ended)), ("fling", None)]);
+        let GestureEvent::Fling { velocity: fling, .. } = ended[1] else { unreachable!() };
+        assert_eq!(fling, velocity);
+    }
+
+    #[test]
+    fn pan_held_still_before_release_doesnt_fling() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        recognizer.process(&UIEvent::PointerDown(finger(1, 0.0, 0.0)), now);
+        recognizer.process(&UIEvent::PointerMove(finger(1, 50.0, 0.0)), now + ms(10));
+        let ended = recognizer.process(&UIEvent::PointerUp(finger(1, 50.0, 0.0)), now + ms(300));
+        assert_eq!(phases(&ended), vec![("pan", Some(GesturePhase::Ended))]);
+        let GestureEvent::Pan { velocity, .. } = ended[0] else { unreachable!() };
+        assert_eq!(velocity, Point::default());
+    }
+
+    #[test]
+    fn cancelled_pan_neither_flings_nor_taps() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        recognizer.process(&UIEvent::PointerDown(finger(1, 0.0, 0.0)), now);
+        recognizer.process(&UIEvent::PointerMove(finger(1, 50.0, 0.0)), now + ms(10));
+        let cancelled = recognizer.process(&UIEvent::PointerCancel(finger(1, 50.0, 0.0)), now + ms(20));
+        assert_eq!(phases(&cancelled), vec![("pan", Some(GesturePhase::Cancelled))]);
+    }
+
+    #[test]
+    fn long_press_after_the_timeout() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        recognizer.process(&UIEvent::PointerDown(finger(1, 10.0, 10.0)), now);
+        assert!(recognizer.take_long_press_request());
+        assert!(!recognizer.take_long_press_request());
+        assert_eq!(recognizer.check_long_press(now + LONG_PRESS_TIME - ms(1)), None);
+        assert_eq!(
+            recognizer.check_long_press(now + LONG_PRESS_TIME),
+            Some(GestureEvent::LongPress { phase: GesturePhase::Began, position: Point::new(10.0, 10.0) }),
+        );
+        assert_eq!(recognizer.check_long_press(now + LONG_PRESS_TIME * 2), None);
+
+        let moved = recognizer.process(&UIEvent::PointerMove(finger(1, 60.0, 10.0)), now + ms(600));
+        assert_eq!(moved, vec![GestureEvent::LongPress { phase: GesturePhase::Changed, position: Point::new(60.0, 10.0) }]);
+        // Neither a pan nor a tap once the long press began.
+        let ended = recognizer.process(&UIEvent::PointerUp(finger(1, 60.0, 10.0)), now + ms(700));
+        assert_eq!(phases(&ended), vec![("long press", Some(GesturePhase::Ended))]);
+    }
+
+    #[test]
+    fn moving_before_the_timeout_prevents_the_long_press() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        recognizer.process(&UIEvent::PointerDown(finger(1, 0.0, 0.0)), now);
+        recognizer.process(&UIEvent::PointerMove(finger(1, 30.0, 0.0)), now + ms(100));
+        assert_eq!(recognizer.check_long_press(now + LONG_PRESS_TIME), None);
+    }
+
+    #[test]
+    fn releasing_before_the_timeout_taps() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        recognizer.process(&UIEvent::PointerDown(finger(1, 0.0, 0.0)), now);
+        assert_eq!(phases(&recognizer.process(&UIEvent::PointerUp(finger(1, 0.0, 0.0)), now + ms(100))), vec![("tap", None)]);
+        assert_eq!(recognizer.check_long_press(now + LONG_PRESS_TIME), None);
+    }
+
+    #[test]
+    fn cancelled_long_press() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        recognizer.process(&UIEvent::PointerDown(finger(1, 0.0, 0.0)), now);
+        recognizer.check_long_press(now + LONG_PRESS_TIME).unwrap();
+        let cancelled = recognizer.process(&UIEvent::PointerCancel(finger(1, 0.0, 0.0)), now + ms(600));
+        assert_eq!(phases(&cancelled), vec![("long press", Some(GesturePhase::Cancelled))]);
+    }
+
+    #[test]
+    fn two_fingers_pinch_and_rotate() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        recognizer.process(&UIEvent::PointerDown(finger(1, 0.0, 0.0)), now);
+        let began = recognizer.process(&UIEvent::PointerDown(finger(2, 100.0, 0.0)), now);
+        assert_eq!(phases(&began), vec![("pinch", Some(GesturePhase::Began)), ("rotate", Some(GesturePhase::Began))]);
+
+        // Twice as far apart.
+        let spread = recognizer.process(&UIEvent::PointerMove(finger(2, 200.0, 0.0)), now + ms(10));
+        let GestureEvent::Pinch { phase, center, scale, scale_delta, velocity } = spread[0] else { panic!("{:?}", spread) };
+        assert_eq!((phase, center, scale, scale_delta), (GesturePhase::Changed, Point::new(100.0, 0.0), 2.0, 2.0));
+        assert!((velocity - 70.0).abs() < 0.01, "{}", velocity);
+
+        // A quarter turn clockwise on screen, at the same distance.
+        let turned = recognizer.process(&UIEvent::PointerMove(finger(2, 0.0, 200.0)), now + ms(20));
+        let GestureEvent::Pinch { scale, scale_delta, .. } = turned[0] else { panic!("{:?}", turned) };
+        assert_eq!((scale, scale_delta), (2.0, 1.0));
+        let GestureEvent::Rotate { phase, rotation, rotation_delta, velocity, .. } = turned[1] else { panic!("{:?}", turned) };
+        assert_eq!(phase, GesturePhase::Changed);
+        assert!((rotation - FRAC_PI_2).abs() < 1e-5 && (rotation_delta - FRAC_PI_2).abs() < 1e-5);
+        assert!(velocity > 0.0);
+
+        let ended = recognizer.process(&UIEvent::PointerUp(finger(1, 0.0, 0.0)), now + ms(30));
+        assert_eq!(phases(&ended), vec![("pinch", Some(GesturePhase::Ended)), ("rotate", Some(GesturePhase::Ended))]);
+        // The remaining finger pans, without a tap when it lifts.
+        let panned = recognizer.process(&UIEvent::PointerMove(finger(2, 0.0, 250.0)), now + ms(40));
+        assert_eq!(phases(&panned), vec![("pan", Some(GesturePhase::Began))]);
+        let lifted = recognizer.process(&UIEvent::PointerUp(finger(2, 0.0, 250.0)), now + ms(500));
+        assert_eq!(phases(&lifted), vec![("pan", Some(GesturePhase::Ended))]);
+    }
+
+    #[test]
+    fn second_finger_ends_the_pan() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        recognizer.process(&UIEvent::PointerDown(finger(1, 0.0, 0.0)), now);
+        recognizer.process(&UIEvent::PointerMove(finger(1, 50.0, 0.0)), now + ms(10));
+        let events = recognizer.process(&UIEvent::PointerDown(finger(2, 100.0, 0.0)), now + ms(20));
+        assert_eq!(
+            phases(&events),
+            vec![("pan", Some(GesturePhase::Ended)), ("pinch", Some(GesturePhase::Began)), ("rotate", Some(GesturePhase::Began))],
+        );
+    }
+
+    #[test]
+    fn cancelled_two_finger_gesture() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        recognizer.process(&UIEvent::PointerDown(finger(1, 0.0, 0.0)), now);
+        recognizer.process(&UIEvent::PointerDown(finger(2, 100.0, 0.0)), now);
+        let cancelled = recognizer.process(&UIEvent::PointerCancel(finger(2, 100.0, 0.0)), now + ms(10));
+        assert_eq!(phases(&cancelled), vec![("pinch", Some(GesturePhase::Cancelled)), ("rotate", Some(GesturePhase::Cancelled))]);
+    }
+
+    #[test]
+    fn touchpad_pinch_at_the_cursor() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        recognizer.process(&UIEvent::PointerMove(mouse(30.0, 40.0)), now);
+        let magnify = |delta: f64, phase: TouchPhase| InputEvent::TouchpadMagnify { delta, phase };
+
+        let began = recognizer.process_touchpad(&magnify(0.0, TouchPhase::Started), now);
+        assert_eq!(began, vec![GestureEvent::Pinch {
+            phase: GesturePhase::Began,
+            center: Point::new(30.0, 40.0),
+            scale: 1.0,
+            scale_delta: 1.0,
+            velocity: 0.0,
+        }]);
+        let changed = recognizer.process_touchpad(&magnify(0.5, TouchPhase::Moved), now + ms(10));
+        let GestureEvent::Pinch { phase, scale, scale_delta, velocity, .. } = changed[0] else { panic!("{:?}", changed) };
+        assert_eq!((phase, scale, scale_delta), (GesturePhase::Changed, 1.5, 1.5));
+        assert!((velocity - 35.0).abs() < 0.01, "{}", velocity);
+        let ended = recognizer.process_touchpad(&magnify(0.0, TouchPhase::Ended), now + ms(20));
+        let GestureEvent::Pinch { phase, scale, .. } = ended[0] else { panic!("{:?}", ended) };
+        assert_eq!((phase, scale), (GesturePhase::Ended, 1.5));
+
+        // The next pinch starts over.
+        let began = recognizer.process_touchpad(&magnify(0.0, TouchPhase::Started), now + ms(30));
+        assert!(matches!(began[0], GestureEvent::Pinch { scale, .. } if scale == 1.0));
+    }
+
+    #[test]
+    fn touchpad_rotation_is_clockwise_positive() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        let rotate = |delta: f32, phase: TouchPhase| InputEvent::TouchpadRotate { delta, phase };
+        recognizer.process_touchpad(&rotate(0.0, TouchPhase::Started), now);
+        // winit reports a counterclockwise quarter turn.
+        let changed = recognizer.process_touchpad(&rotate(90.0, TouchPhase::Moved), now + ms(10));
+        let GestureEvent::Rotate { phase, rotation, rotation_delta, velocity, .. } = changed[0] else { panic!("{:?}", changed) };
+        assert_eq!(phase, GesturePhase::Changed);
+        assert!((rotation + FRAC_PI_2).abs() < 1e-5 && (rotation_delta + FRAC_PI_2).abs() < 1e-5);
+        assert!(velocity < 0.0);
+        let cancelled = recognizer.process_touchpad(&rotate(0.0, TouchPhase::Cancelled), now + ms(20));
+        assert_eq!(phases(&cancelled), vec![("rotate", Some(GesturePhase::Cancelled))]);
+    }
+
+    #[test]
+    fn smart_magnify_at_the_cursor() {
+        let mut recognizer = GestureRecognizer::default();
+        let now = Instant::now();
+        recognizer.process(&UIEvent::PointerMove(mouse(5.0, 6.0)), now);
+        assert_eq!(
+            recognizer.process_touchpad(&InputEvent::SmartMagnify, now),
+            vec![GestureEvent::SmartMagnify { position: Point::new(5.0, 6.0) }],
+        );
+    }
+}
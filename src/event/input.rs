@@ -0,0 +1,535 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use skia_safe::Point;
+use winit::dpi::PhysicalPosition;
+use winit::event::{Ime, MouseButton, TouchPhase};
+use winit::keyboard::{Key, KeyLocation, ModifiersState, PhysicalKey};
+use crate::event::input_event::InputEvent;
+use crate::event::ui_event::UIEvent;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum PointerButton {
+    Primary,
+    Secondary,
+    Middle,
+    Back,
+    Forward,
+    Other(u16),
+}
+
+impl From<MouseButton> for PointerButton {
+    fn from(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left => PointerButton::Primary,
+            MouseButton::Right => PointerButton::Secondary,
+            MouseButton::Middle => PointerButton::Middle,
+            MouseButton::Back => PointerButton::Back,
+            MouseButton::Forward => PointerButton::Forward,
+            MouseButton::Other(id) => PointerButton::Other(id),
+        }
+    }
+}
+
+/// The set of buttons held down on a pointer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct PointerButtons(BTreeSet<PointerButton>);
+
+impl PointerButtons {
+    pub fn contains(&self, button: PointerButton) -> bool {
+        self.0.contains(&button)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = PointerButton> + '_ {
+        self.0.iter().copied()
+    }
+
+    fn insert(&mut self, button: PointerButton) {
+        self.0.insert(button);
+    }
+
+    fn remove(&mut self, button: PointerButton) {
+        self.0.remove(&button);
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PointerId {
+    Mouse,
+    /// A finger or stylus, the id is unique while it touches the screen.
+    Touch(u64),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PointerType {
+    Mouse,
+    Touch,
+    Pen,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    /// The Windows key on PC keyboards, Command on Apple keyboards.
+    pub meta: bool,
+}
+
+impl From<ModifiersState> for Modifiers {
+    fn from(state: ModifiersState) -> Self {
+        Self {
+            shift: state.shift_key(),
+            control: state.control_key(),
+            alt: state.alt_key(),
+            meta: state.super_key(),
+        }
+    }
+}
+
+/// Mouse, touch and pen input share this event. Positions are in logical pixels,
+/// relative to the top-left corner of the window's client area.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointerEvent {
+    pub pointer_id: PointerId,
+    pub pointer_type: PointerType,
+    pub position: Point,
+    /// The button that changed, only set for `PointerDown` and `PointerUp`.
+    pub button: Option<PointerButton>,
+    /// Buttons held down after this event.
+    pub buttons: PointerButtons,
+    /// From `0` to `1`. Devices that can't measure it report `0.5` while pressed and `0` otherwise.
+    pub pressure: f32,
+    /// `2` for a double click, `3` for a triple click and so on, `0` for events other than `PointerDown` and `PointerUp`.
+    pub click_count: u32,
+    pub modifiers: Modifiers,
+}
+
+/// Files dragged from another application over the window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDragEvent {
+    pub paths: Vec<PathBuf>,
+    /// Logical position of the cursor. Most platforms don't report the cursor while dragging,
+    /// it is then the last position known before the drag.
+    pub position: Point,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WheelDelta {
+    /// Lines or rows to scroll, as reported by most mouse wheels.
+    Lines(f32, f32),
+    /// Logical pixels to scroll, as reported by touchpads.
+    Pixels(f32, f32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WheelEvent {
+    pub position: Point,
+    pub delta: WheelDelta,
+    pub modifiers: Modifiers,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardEvent {
+    /// The key with the keyboard layout applied, use it for shortcuts and text editing commands.
+    pub key: Key,
+    /// The key's position on the keyboard regardless of layout, use it for game-style controls.
+    pub physical_key: PhysicalKey,
+    pub location: KeyLocation,
+    pub repeat: bool,
+    pub modifiers: Modifiers,
+}
+
+/// Farthest a pointer can move between the presses of a double click, in logical pixels.
+const CLICK_SLOP: f32 = 4.0;
+const TOUCH_CLICK_SLOP: f32 = 16.0;
+
+#[cfg(target_os = "windows")]
+fn double_click_time() -> Duration {
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetDoubleClickTime() -> u32;
+    }
+    Duration::from_millis(unsafe { GetDoubleClickTime() } as u64)
+}
+
+#[cfg(target_os = "macos")]
+fn double_click_time() -> Duration {
+    let interval = objc2_app_kit::NSEvent::doubleClickInterval();
+    Duration::try_from_secs_f64(interval).unwrap_or(Duration::from_millis(500))
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+pub(crate) fn load_double_click_time() {}
+
+/// Read by `load_double_click_time`, GTK's default until then.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+static DOUBLE_CLICK_TIME: std::sync::OnceLock<Duration> = std::sync::OnceLock::new();
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn double_click_time() -> Duration {
+    DOUBLE_CLICK_TIME.get().copied().unwrap_or(Duration::from_millis(400))
+}
+
+/// Reads GNOME's setting, which GTK follows, then the one of GTK's own settings files. That runs
+/// `gsettings`, so it happens once on a background thread rather than on the event loop.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn load_double_click_time() {
+    static LOAD: std::sync::Once = std::sync::Once::new();
+    LOAD.call_once(|| {
+        std::thread::spawn(|| {
+            if let Some(millis) = read_double_click_time() {
+                let _ = DOUBLE_CLICK_TIME.set(Duration::from_millis(millis));
+            }
+        });
+    });
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn read_double_click_time() -> Option<u64> {
+    let gsettings = std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.peripherals.mouse", "double-click"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .and_then(|millis| millis.trim().trim_start_matches("int32 ").parse().ok());
+    let gtk_settings = || {
+        let config_dir = dirs::config_dir()?;
+        ["gtk-4.0", "gtk-3.0"].iter().find_map(|version| {
+            let settings = std::fs::read_to_string(config_dir.join(version).join("settings.ini")).ok()?;
+            settings.lines().find_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                if key.trim() != "gtk-double-click-time" {
+                    return None;
+                }
+                value.trim().parse().ok()
+            })
+        })
+    };
+    gsettings.or_else(gtk_settings)
+}
+
+struct Click {
+    pointer_type: PointerType,
+    button: PointerButton,
+    position: Point,
+    time: Instant,
+    count: u32,
+}
+
+/// Turns input into typed `UIEvent`s, keeping the state winit only reports separately.
+#[derive(Default)]
+pub(crate) struct InputState {
+    cursor_position: Point,
+    cursor_inside: bool,
+    modifiers: Modifiers,
+    buttons: PointerButtons,
+    /// Set by touchpads with force sensing.
+    mouse_pressure: Option<f32>,
+    last_click: Option<Click>,
+    /// Click count of every button held down, repeated by its `PointerUp`.
+    press_counts: HashMap<(PointerId, PointerButton), u32>,
+    /// The system setting is used if `None`.
+    double_click_time: Option<Duration>,
+}
+
+impl InputState {
+    #[cfg(test)]
+    pub(crate) fn with_double_click_time(double_click_time: Duration) -> Self {
+        Self {
+            double_click_time: Some(double_click_time),
+            ..Self::default()
+        }
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    pub fn cursor_position(&self) -> Point {
+        self.cursor_position
+    }
+
+    /// Returns an empty list for input that has no typed equivalent, such as modifier changes.
+    /// While the mouse is in `captures`, leaving and re-entering the window isn't reported.
+    pub fn translate(&mut self, event: &InputEvent, now: Instant, captures: &[PointerId]) -> Vec<UIEvent> {
+        let mouse_captured = captures.contains(&PointerId::Mouse);
+        match event {
+            InputEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = *modifiers;
+                vec![]
+            }
+            InputEvent::Key { key, physical_key, location, pressed, repeat, text } => {
+                let keyboard_event = KeyboardEvent {
+                    key: key.clone(),
+                    physical_key: *physical_key,
+                    location: *location,
+                    repeat: *repeat,
+                    modifiers: self.modifiers,
+                };
+                if !pressed {
+                    return vec![UIEvent::KeyUp(keyboard_event)];
+                }
+                let mut events = vec![UIEvent::KeyDown(keyboard_event)];
+                // Shortcuts such as Ctrl+C still carry text on some platforms, it isn't typed text.
+                let typing = !self.modifiers.control && !self.modifiers.meta;
+                if let Some(text) = text {
+                    if typing && !text.chars().any(char::is_control) {
+                        events.push(UIEvent::TextInput(text.clone()));
+                    }
+                }
+                events
+            }
+            InputEvent::Ime(ime) => match ime {
+                Ime::Enabled => vec![UIEvent::ImeEnabled],
+                Ime::Preedit(text, cursor) => vec![UIEvent::ImePreedit(text.clone(), *cursor)],
+                Ime::Commit(text) => vec![UIEvent::ImeCommit(text.clone())],
+                Ime::Disabled => vec![UIEvent::ImeDisabled],
+            },
+            InputEvent::CursorMoved { x, y } => {
+                self.cursor_position = Point::new(*x, *y);
+                vec![UIEvent::PointerMove(self.mouse_event(None, 0))]
+            }
+            InputEvent::CursorEntered => {
+                self.cursor_inside = true;
+                if mouse_captured {
+                    return vec![];
+                }
+                vec![UIEvent::PointerEnter(self.mouse_event(None, 0))]
+            }
+            InputEvent::CursorLeft => {
+                self.cursor_inside = false;
+                if mouse_captured {
+                    return vec![];
+                }
+                vec![UIEvent::PointerLeave(self.mouse_event(None, 0))]
+            }
+            InputEvent::MouseInput { button, pressed: true } => {
+                self.buttons.insert(*button);
+                let click_count = self.count_click(PointerId::Mouse, PointerType::Mouse, *button, self.cursor_position, now);
+                vec![UIEvent::PointerDown(self.mouse_event(Some(*button), click_count))]
+            }
+            InputEvent::MouseInput { button, pressed: false } => {
+                self.buttons.remove(*button);
+                let click_count = self.press_counts.remove(&(PointerId::Mouse, *button)).unwrap_or(1);
+                let mut events = vec![UIEvent::PointerUp(self.mouse_event(Some(*button), click_count))];
+                // The leave was held back by the capture, which ends with the last button.
+                if mouse_captured && self.buttons.is_empty() && !self.cursor_inside {
+                    events.push(UIEvent::PointerLeave(self.mouse_event(None, 0)));
+                }
+                events
+            }
+            InputEvent::TouchpadPressure { pressure, .. } => {
+                self.mouse_pressure = Some(pressure.clamp(0.0, 1.0));
+                vec![UIEvent::PointerMove(self.mouse_event(None, 0))]
+            }
+            InputEvent::Touch { id, phase, x, y, force, pen } => self.translate_touch(*id, *phase, Point::new(*x, *y), *force, *pen, now),
+            InputEvent::MouseWheel { delta } => vec![UIEvent::Wheel(WheelEvent {
+                position: self.cursor_position,
+                delta: *delta,
+                modifiers: self.modifiers,
+            })],
+            InputEvent::TouchpadMagnify { .. } | InputEvent::TouchpadRotate { .. } | InputEvent::SmartMagnify => vec![],
+        }
+    }
+
+    fn translate_touch(&mut self, id: u64, phase: TouchPhase, position: Point, force: Option<f32>, pen: bool, now: Instant) -> Vec<UIEvent> {
+        let pointer_type = if pen { PointerType::Pen } else { PointerType::Touch };
+        let mut pointer_event = PointerEvent {
+            pointer_id: PointerId::Touch(id),
+            pointer_type,
+            position,
+            button: None,
+            buttons: PointerButtons::default(),
+            pressure: force.unwrap_or(0.5),
+            click_count: 0,
+            modifiers: self.modifiers,
+        };
+        pointer_event.buttons.insert(PointerButton::Primary);
+        match phase {
+            TouchPhase::Started => {
+                pointer_event.button = Some(PointerButton::Primary);
+                pointer_event.click_count = self.count_click(PointerId::Touch(id), pointer_type, PointerButton::Primary, position, now);
+                vec![UIEvent::PointerDown(pointer_event)]
+            }
+            TouchPhase::Moved => {
+                vec![UIEvent::PointerMove(pointer_event)]
+            }
+            TouchPhase::Ended => {
+                pointer_event.button = Some(PointerButton::Primary);
+                pointer_event.buttons = PointerButtons::default();
+                pointer_event.pressure = 0.0;
+                pointer_event.click_count = self.press_counts.remove(&(PointerId::Touch(id), PointerButton::Primary)).unwrap_or(1);
+                vec![UIEvent::PointerUp(pointer_event)]
+            }
+            TouchPhase::Cancelled => {
+                self.press_counts.remove(&(PointerId::Touch(id), PointerButton::Primary));
+                pointer_event.buttons = PointerButtons::default();
+                pointer_event.pressure = 0.0;
+                vec![UIEvent::PointerCancel(pointer_event)]
+            }
+        }
+    }
+
+    fn count_click(&mut self, pointer_id: PointerId, pointer_type: PointerType, button: PointerButton, position: Point, now: Instant) -> u32 {
+        let slop = if pointer_type == PointerType::Mouse { CLICK_SLOP } else { TOUCH_CLICK_SLOP };
+        let double_click_time = self.double_click_time.unwrap_or_else(double_click_time);
+        let count = match &self.last_click {
+            Some(click) if click.pointer_type == pointer_type
+                && click.button == button
+                && now.saturating_duration_since(click.time) <= double_click_time
+                && (click.position.x - position.x).abs() <= slop
+                && (click.position.y - position.y).abs() <= slop => click.count + 1,
+            _ => 1,
+        };
+        self.last_click = Some(Click {
+            pointer_type,
+            button,
+            position,
+            time: now,
+            count,
+        });
+        self.press_counts.insert((pointer_id, button), count);
+        count
+    }
+
+    fn mouse_event(&self, button: Option<PointerButton>, click_count: u32) -> PointerEvent {
+        let pressure = match self.buttons.is_empty() {
+            true => 0.0,
+            false => self.mouse_pressure.unwrap_or(0.5),
+        };
+        PointerEvent {
+            pointer_id: PointerId::Mouse,
+            pointer_type: PointerType::Mouse,
+            position: self.cursor_position,
+            button,
+            buttons: self.buttons.clone(),
+            pressure,
+            click_count,
+            modifiers: self.modifiers,
+        }
+    }
+}
+
+pub(crate) fn logical_point(position: PhysicalPosition<f64>, scale_factor: f64) -> Point {
+    let position = position.to_logical::<f32>(scale_factor);
+    Point::new(position.x, position.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+    fn click_counts(events: &[UIEvent]) -> Vec<(&'static str, u32)> {
+        events.iter()
+            .filter_map(|event| match event {
+                UIEvent::PointerDown(pointer) => Some(("down", pointer.click_count)),
+                UIEvent::PointerUp(pointer) => Some(("up", pointer.click_count)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn mouse(state: &mut InputState, button: PointerButton, pressed: bool, now: Instant) -> Vec<(&'static str, u32)> {
+        click_counts(&state.translate(&InputEvent::MouseInput { button, pressed }, now, &[]))
+    }
+
+    fn move_to(state: &mut InputState, x: f32, y: f32, now: Instant) {
+        state.translate(&InputEvent::CursorMoved { x, y }, now, &[]);
+    }
+
+    #[test]
+    fn quick_clicks_in_place_count_up() {
+        let mut state = InputState::with_double_click_time(DOUBLE_CLICK_TIME);
+        let start = Instant::now();
+        for (index, count) in [1, 2, 3].into_iter().enumerate() {
+            let now = start + Duration::from_millis(100) * index as u32;
+            assert_eq!(mouse(&mut state, PointerButton::Primary, true, now), vec![("down", count)]);
+            assert_eq!(mouse(&mut state, PointerButton::Primary, false, now), vec![("up", count)]);
+        }
+    }
+
+    #[test]
+    fn slow_click_starts_over() {
+        let mut state = InputState::with_double_click_time(DOUBLE_CLICK_TIME);
+        let start = Instant::now();
+        mouse(&mut state, PointerButton::Primary, true, start);
+        mouse(&mut state, PointerButton::Primary, false, start);
+        let later = start + DOUBLE_CLICK_TIME + Duration::from_millis(1);
+        assert_eq!(mouse(&mut state, PointerButton::Primary, true, later), vec![("down", 1)]);
+    }
+
+    #[test]
+    fn click_elsewhere_starts_over() {
+        let mut state = InputState::with_double_click_time(DOUBLE_CLICK_TIME);
+        let now = Instant::now();
+        move_to(&mut state, 10.0, 10.0, now);
+        mouse(&mut state, PointerButton::Primary, true, now);
+        mouse(&mut state, PointerButton::Primary, false, now);
+        move_to(&mut state, 10.0 + CLICK_SLOP, 10.0, now);
+        assert_eq!(mouse(&mut state, PointerButton::Primary, true, now), vec![("down", 2)]);
+        mouse(&mut state, PointerButton::Primary, false, now);
+        move_to(&mut state, 20.0, 10.0, now);
+        assert_eq!(mouse(&mut state, PointerButton::Primary, true, now), vec![("down", 1)]);
+    }
+
+    #[test]
+    fn other_button_starts_over_and_keeps_each_count() {
+        let mut state = InputState::with_double_click_time(DOUBLE_CLICK_TIME);
+        let now = Instant::now();
+        mouse(&mut state, PointerButton::Primary, true, now);
+        mouse(&mut state, PointerButton::Primary, false, now);
+        assert_eq!(mouse(&mut state, PointerButton::Primary, true, now), vec![("down", 2)]);
+        assert_eq!(mouse(&mut state, PointerButton::Secondary, true, now), vec![("down", 1)]);
+        // Released in the other order, each button repeats the count of its own press.
+        assert_eq!(mouse(&mut state, PointerButton::Primary, false, now), vec![("up", 2)]);
+        assert_eq!(mouse(&mut state, PointerButton::Secondary, false, now), vec![("up", 1)]);
+    }
+
+    #[test]
+    fn taps_count_with_a_wider_slop() {
+        let mut state = InputState::with_double_click_time(DOUBLE_CLICK_TIME);
+        let now = Instant::now();
+        let touch = |phase, x| InputEvent::Touch { id: 1, phase, x, y: 0.0, force: None, pen: false };
+        state.translate(&touch(TouchPhase::Started, 0.0), now, &[]);
+        state.translate(&touch(TouchPhase::Ended, 0.0), now, &[]);
+        let events = state.translate(&touch(TouchPhase::Started, TOUCH_CLICK_SLOP), now, &[]);
+        assert_eq!(click_counts(&events), vec![("down", 2)]);
+        let events = state.translate(&touch(TouchPhase::Ended, TOUCH_CLICK_SLOP), now, &[]);
+        assert_eq!(click_counts(&events), vec![("up", 2)]);
+    }
+
+    #[test]
+    fn capture_holds_the_leave_until_release() {
+        let mut state = InputState::with_double_click_time(DOUBLE_CLICK_TIME);
+        let now = Instant::now();
+        let captures = [PointerId::Mouse];
+        state.translate(&InputEvent::CursorEntered, now, &[]);
+        state.translate(&InputEvent::MouseInput { button: PointerButton::Primary, pressed: true }, now, &captures);
+        assert!(state.translate(&InputEvent::CursorLeft, now, &captures).is_empty());
+        let events = state.translate(&InputEvent::CursorMoved { x: -50.0, y: 0.0 }, now, &captures);
+        assert!(matches!(events.as_slice(), [UIEvent::PointerMove(pointer)] if pointer.position.x == -50.0));
+        let events = state.translate(&InputEvent::MouseInput { button: PointerButton::Primary, pressed: false }, now, &captures);
+        assert!(matches!(events.as_slice(), [UIEvent::PointerUp(_), UIEvent::PointerLeave(_)]));
+    }
+
+    #[test]
+    fn extra_buttons_are_told_apart() {
+        let mut state = InputState::with_double_click_time(DOUBLE_CLICK_TIME);
+        let now = Instant::now();
+        mouse(&mut state, PointerButton::Other(30), true, now);
+        mouse(&mut state, PointerButton::Other(40), true, now);
+        mouse(&mut state, PointerButton::Other(30), false, now);
+        assert!(state.buttons.contains(PointerButton::Other(40)));
+        assert!(!state.buttons.contains(PointerButton::Other(30)));
+        assert_eq!(state.buttons.iter().collect::<Vec<_>>(), vec![PointerButton::Other(40)]);
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use winit::event::{ElementState, Force, Ime, MouseScrollDelta, TouchPhase, WindowEvent};
+use winit::keyboard::{Key, KeyLocation, PhysicalKey};
+use crate::event::input::{logical_point, Modifiers, PointerButton, WheelDelta};
+
+/// Keyboard, pointer and touch input of a window, before it is turned into `UIEvent`s.
+/// Unlike winit's events it can be saved, replayed and created by hand. Positions are in logical pixels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InputEvent {
+    CursorMoved {
+        x: f32,
+        y: f32,
+    },
+    CursorEntered,
+    CursorLeft,
+    MouseInput {
+        button: PointerButton,
+        pressed: bool,
+    },
+    MouseWheel {
+        delta: WheelDelta,
+    },
+    ModifiersChanged(Modifiers),
+    Key {
+        key: Key,
+        physical_key: PhysicalKey,
+        location: KeyLocation,
+        pressed: bool,
+        repeat: bool,
+        /// Text the key types, if any.
+        text: Option<String>,
+    },
+    Ime(Ime),
+    Touch {
+        id: u64,
+        phase: TouchPhase,
+        x: f32,
+        y: f32,
+        /// Normalized from `0` to `1`, when the device measures it.
+        force: Option<f32>,
+        /// Whether it comes from a stylus rather than a finger.
+        pen: bool,
+    },
+    TouchpadPressure {
+        pressure: f32,
+        stage: i64,
+    },
+    TouchpadMagnify {
+        delta: f64,
+        phase: TouchPhase,
+    },
+    /// `delta` is in degrees, counterclockwise positive.
+    TouchpadRotate {
+        delta: f32,
+        phase: TouchPhase,
+    },
+    SmartMagnify,
+}
+
+impl InputEvent {
+    /// Returns `None` for window events that aren't input, such as resizes.
+    pub fn from_window_event(event: &WindowEvent, scale_factor: f64) -> Option<Self> {
+        let input = match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = logical_point(*position, scale_factor);
+                InputEvent::CursorMoved {
+                    x: position.x,
+                    y: position.y,
+                }
+            }
+            WindowEvent::CursorEntered { .. } => InputEvent::CursorEntered,
+            WindowEvent::CursorLeft { .. } => InputEvent::CursorLeft,
+            WindowEvent::MouseInput { state, button, .. } => InputEvent::MouseInput {
+                button: PointerButton::from(*button),
+                pressed: *state == ElementState::Pressed,
+            },
+            WindowEvent::MouseWheel { delta, .. } => {
+                let delta = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => WheelDelta::Lines(*x, *y),
+                    MouseScrollDelta::PixelDelta(delta) => {
+                        let delta = delta.to_logical::<f32>(scale_factor);
+                        WheelDelta::Pixels(delta.x, delta.y)
+                    }
+                };
+                InputEvent::MouseWheel { delta }
+            }
+            WindowEvent::ModifiersChanged(modifiers) => InputEvent::ModifiersChanged(modifiers.state().into()),
+            WindowEvent::KeyboardInput { event, .. } => InputEvent::Key {
+                key: event.logical_key.clone(),
+                physical_key: event.physical_key,
+                location: event.location,
+                pressed: event.state == ElementState::Pressed,
+                repeat: event.repeat,
+                text: event.text.as_ref().map(|text| text.to_string()),
+            },
+            WindowEvent::Ime(ime) => InputEvent::Ime(ime.clone()),
+            WindowEvent::Touch(touch) => {
+                let position = logical_point(touch.location, scale_factor);
+                InputEvent::Touch {
+                    id: touch.id,
+                    phase: touch.phase,
+                    x: position.x,
+                    y: position.y,
+                    force: touch.force.map(|force| force.normalized() as f32),
+                    pen: matches!(touch.force, Some(Force::Calibrated { altitude_angle: Some(_), .. })),
+                }
+            }
+            WindowEvent::TouchpadPressure { pressure, stage, .. } => InputEvent::TouchpadPressure {
+                pressure: *pressure,
+                stage: *stage,
+            },
+            WindowEvent::TouchpadMagnify { delta, phase, .. } => InputEvent::TouchpadMagnify {
+                delta: *delta,
+                phase: *phase,
+            },
+            WindowEvent::TouchpadRotate { delta, phase, .. } => InputEvent::TouchpadRotate {
+                delta: *delta,
+                phase: *phase,
+            },
+            WindowEvent::SmartMagnify { .. } => InputEvent::SmartMagnify,
+            _ => return None,
+        };
+        Some(input)
+    }
+}
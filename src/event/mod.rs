@@ -1 +1,8 @@
-pub mod ui_event;
\ No newline at end of file
+pub mod ui_event;
+pub mod window_message;
+pub mod input;
+pub mod input_event;
+pub mod gesture;
+pub(crate) mod pipeline;
+pub mod recording;
+pub mod synthetic;
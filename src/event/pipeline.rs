@@ -0,0 +1,36 @@
+#[cfg(test)]
+use std::time::Duration;
+use std::time::Instant;
+use crate::event::gesture::GestureRecognizer;
+use crate::event::input::{InputState, PointerId};
+use crate::event::input_event::InputEvent;
+use crate::event::ui_event::UIEvent;
+
+/// The part of a window's input handling that only depends on the input: pointer state and gesture
+/// recognition. Shortcuts and delivery to the UI stay with the window.
+#[derive(Default)]
+pub(crate) struct InputPipeline {
+    pub input: InputState,
+    pub gestures: GestureRecognizer,
+}
+
+impl InputPipeline {
+    #[cfg(test)]
+    pub(crate) fn with_double_click_time(double_click_time: Duration) -> Self {
+        Self {
+            input: InputState::with_double_click_time(double_click_time),
+            gestures: GestureRecognizer::default(),
+        }
+    }
+
+    /// The `UIEvent`s `input` turns into, followed by the gestures they complete.
+    pub fn process(&mut self, input: &InputEvent, now: Instant, captures: &[PointerId]) -> Vec<UIEvent> {
+        let mut ui_events = self.input.translate(input, now, captures);
+        let mut gestures = self.gestures.process_touchpad(input, now);
+        for ui_event in &ui_events {
+            gestures.extend(self.gestures.process(ui_event, now));
+        }
+        ui_events.extend(gestures.into_iter().map(UIEvent::Gesture));
+        ui_events
+    }
+}
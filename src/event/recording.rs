@@ -0,0 +1,215 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::event::input_event::InputEvent;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedInput {
+    /// Time since the window was created.
+    pub time: Duration,
+    pub event: InputEvent,
+}
+
+/// Input delivered to a window, started with `WindowContext::start_recording`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    pub events: Vec<RecordedInput>,
+}
+
+impl InputRecording {
+    /// Reads a recording saved with `save`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        let content = fs::read(path).map_err(RecordingError::Io)?;
+        serde_json::from_slice(&content).map_err(RecordingError::Json)
+    }
+
+    /// Writes the recording as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RecordingError> {
+        let content = serde_json::to_vec_pretty(self).map_err(RecordingError::Json)?;
+        fs::write(path, content).map_err(RecordingError::Io)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ReplaySpeed {
+    /// Keeps the recorded timing, relative to the creation of the window replayed into.
+    RealTime,
+    /// The whole recording at once, in the next event loop iteration.
+    AsFastAsPossible,
+}
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl Display for RecordingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordingError::Io(error) => write!(f, "failed to access input recording: {}", error),
+            RecordingError::Json(error) => write!(f, "invalid input recording: {}", error),
+        }
+    }
+}
+
+impl Error for RecordingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RecordingError::Io(error) => Some(error),
+            RecordingError::Json(error) => Some(error),
+        }
+    }
+}
+
+/// A recording being replayed into a window.
+pub(crate) struct Replay {
+    events: VecDeque<RecordedInput>,
+    speed: ReplaySpeed,
+    /// Instant the recorded times count from, set when the first event is due.
+    origin: Option<Instant>,
+}
+
+impl Replay {
+    pub fn new(recording: InputRecording, speed: ReplaySpeed) -> Self {
+        Self {
+            events: recording.events.into(),
+            speed,
+            origin: None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// When the next event is due, `None` once finished.
+    pub fn next_due(&self, created_at: Instant, now: Instant) -> Option<Instant> {
+        let first = self.events.front()?;
+        match (self.speed, self.origin) {
+            (ReplaySpeed::RealTime, Some(origin)) => Some(origin + first.time),
+            // A replay started late is shifted to start right away, see `take_due`.
+            (ReplaySpeed::RealTime, None) => Some((created_at + first.time).max(now)),
+            (ReplaySpeed::AsFastAsPossible, _) => Some(now),
+        }
+    }
+
+    /// The events due at `now`. A replay started after the time of its first event is shifted
+    /// to start right away, keeping the time between events.
+    pub fn take_due(&mut self, created_at: Instant, now: Instant) -> Vec<InputEvent> {
+        let Some(first) = self.events.front() else {
+            return vec![];
+        };
+        if self.speed == ReplaySpeed::AsFastAsPossible {
+            return self.events.drain(..).map(|recorded| recorded.event).collect();
+        }
+        let first_time = first.time;
+        let origin = *self.origin.get_or_insert_with(|| match now.checked_sub(first_time) {
+            Some(shifted) if shifted > created_at => shifted,
+            _ => created_at,
+        });
+        let mut due = vec![];
+        while let Some(recorded) = self.events.front() {
+            if origin + recorded.time > now {
+                break;
+            }
+            due.extend(self.events.pop_front().map(|recorded| recorded.event));
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::event::TouchPhase;
+    use winit::keyboard::{Key, KeyCode, KeyLocation, PhysicalKey, SmolStr};
+    use crate::event::input::{Modifiers, PointerButton, WheelDelta};
+    use crate::event::pipeline::InputPipeline;
+    use crate::event::ui_event::UIEvent;
+    use super::*;
+
+    fn pipeline() -> InputPipeline {
+        InputPipeline::with_double_click_time(Duration::from_millis(400))
+    }
+
+    fn session() -> Vec<(u64, InputEvent)> {
+        let key = |pressed| InputEvent::Key {
+            key: Key::Character(SmolStr::new("a")),
+            physical_key: PhysicalKey::Code(KeyCode::KeyA),
+            location: KeyLocation::Standard,
+            pressed,
+            repeat: false,
+            text: Some("a".to_string()).filter(|_| pressed),
+        };
+        let touch = |phase, x| InputEvent::Touch { id: 7, phase, x, y: 40.0, force: None, pen: false };
+        vec![
+            (5, InputEvent::CursorEntered),
+            (10, InputEvent::CursorMoved { x: 20.0, y: 30.0 }),
+            (20, InputEvent::MouseInput { button: PointerButton::Primary, pressed: true }),
+            (60, InputEvent::MouseInput { button: PointerButton::Primary, pressed: false }),
+            (150, InputEvent::MouseInput { button: PointerButton::Primary, pressed: true }),
+            (190, InputEvent::MouseInput { button: PointerButton::Primary, pressed: false }),
+            (300, InputEvent::ModifiersChanged(Modifiers { shift: true, ..Modifiers::default() })),
+            (310, key(true)),
+            (350, key(false)),
+            (400, InputEvent::MouseWheel { delta: WheelDelta::Lines(0.0, -2.0) }),
+            (500, touch(TouchPhase::Started, 10.0)),
+            (520, touch(TouchPhase::Moved, 60.0)),
+            (540, touch(TouchPhase::Ended, 120.0)),
+            (600, InputEvent::CursorLeft),
+        ]
+    }
+
+    #[test]
+    fn replayed_recording_delivers_the_same_events() {
+        let created_at = Instant::now();
+        let mut original = pipeline();
+        let mut recording = InputRecording::default();
+        let mut expected = vec![];
+        for (millis, event) in session() {
+            let time = Duration::from_millis(millis);
+            expected.extend(original.process(&event, created_at + time, &[]));
+            recording.events.push(RecordedInput { time, event });
+        }
+
+        let json = serde_json::to_string(&recording).unwrap();
+        let loaded: InputRecording = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, recording);
+
+        // Replayed into a window created later, the events keep their timing relative to it.
+        let replay_created_at = created_at + Duration::from_secs(10);
+        let mut replay = Replay::new(loaded, ReplaySpeed::RealTime);
+        let mut replayed = pipeline();
+        let mut actual = vec![];
+        let mut now = replay_created_at;
+        while let Some(due) = replay.next_due(replay_created_at, now) {
+            now = due;
+            for event in replay.take_due(replay_created_at, now) {
+                actual.extend(replayed.process(&event, now, &[]));
+            }
+        }
+        assert!(expected.iter().any(|event| matches!(event, UIEvent::PointerDown(pointer) if pointer.click_count == 2)));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn as_fast_as_possible_delivers_everything_at_once() {
+        let recording = InputRecording {
+            events: session().into_iter()
+                .map(|(millis, event)| RecordedInput { time: Duration::from_millis(millis), event })
+                .collect(),
+        };
+        let events: Vec<InputEvent> = recording.events.iter().map(|recorded| recorded.event.clone()).collect();
+        let now = Instant::now();
+        let mut replay = Replay::new(recording, ReplaySpeed::AsFastAsPossible);
+        assert_eq!(replay.next_due(now, now), Some(now));
+        assert_eq!(replay.take_due(now, now), events);
+        assert!(replay.is_finished());
+        assert_eq!(replay.next_due(now, now), None);
+    }
+}
@@ -0,0 +1,207 @@
+use winit::dpi::LogicalSize;
+use winit::event::WindowEvent;
+use winit::keyboard::{Key, KeyLocation, NativeKeyCode, PhysicalKey, SmolStr};
+use crate::event::input::{PointerButton, WheelDelta};
+use crate::event::input_event::InputEvent;
+
+/// An event pushed into a window with `Context::inject_event`, handled as if it came from the system.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntheticEvent {
+    /// Goes through pointer capture, popups, modals, shortcuts and gesture recognition like system input.
+    /// Input that produces no `UIEvent` isn't handed to the UI as `UIEvent::Other`, there is no winit event behind it.
+    Input(InputEvent),
+    /// New logical size of the window content: the rendering surface is resized and the UI gets
+    /// `UIEvent::Resize`, but the window itself keeps its size on screen.
+    Resize {
+        width: f32,
+        height: f32,
+    },
+    Focus(bool),
+    /// Any other winit event, delivered as is.
+    Window(WindowEvent),
+}
+
+impl SyntheticEvent {
+    /// Moves the cursor to `x`, `y`, then presses and releases `button`.
+    pub fn click(x: f32, y: f32, button: PointerButton) -> Vec<Self> {
+        vec![
+            InputEvent::CursorMoved { x, y }.into(),
+            InputEvent::MouseInput { button, pressed: true }.into(),
+            InputEvent::MouseInput { button, pressed: false }.into(),
+        ]
+    }
+
+    /// Presses and releases a key, `text` being what the key types, if anything.
+    /// Shortcuts see the key as well, set modifiers before with `InputEvent::ModifiersChanged`.
+    pub fn key_press(key: Key, physical_key: PhysicalKey, text: Option<&str>) -> Vec<Self> {
+        let key_event = |pressed| InputEvent::Key {
+            key: key.clone(),
+            physical_key,
+            location: KeyLocation::Standard,
+            pressed,
+            repeat: false,
+            text: text.filter(|_| pressed).map(String::from),
+        };
+        vec![key_event(true).into(), key_event(false).into()]
+    }
+
+    /// A key press for every character of `text`. There is no physical key behind them, so
+    /// shortcuts bound to physical keys don't match.
+    pub fn type_text(text: &str) -> Vec<Self> {
+        text.chars()
+            .flat_map(|char| {
+                let text = char.to_string();
+                let key = Key::Character(SmolStr::new(&text));
+                Self::key_press(key, PhysicalKey::Unidentified(NativeKeyCode::Unidentified), Some(&text))
+            })
+            .collect()
+    }
+
+    /// Moves the cursor to `x`, `y` and scrolls by `delta` there.
+    pub fn scroll(x: f32, y: f32, delta: WheelDelta) -> Vec<Self> {
+        vec![
+            InputEvent::CursorMoved { x, y }.into(),
+            InputEvent::MouseWheel { delta }.into(),
+        ]
+    }
+
+    pub fn resize(width: f32, height: f32) -> Self {
+        SyntheticEvent::Resize { width, height }
+    }
+}
+
+/// What a synthetic event is delivered as.
+pub(crate) enum Delivery {
+    Input(InputEvent),
+    Window(WindowEvent),
+}
+
+impl SyntheticEvent {
+    pub(crate) fn into_delivery(self, scale_factor: f64) -> Delivery {
+        match self {
+            SyntheticEvent::Input(input) => Delivery::Input(input),
+            SyntheticEvent::Resize { width, height } => {
+                Delivery::Window(WindowEvent::Resized(LogicalSize::new(width, height).to_physical(scale_factor)))
+            }
+            SyntheticEvent::Focus(focused) => Delivery::Window(WindowEvent::Focused(focused)),
+            SyntheticEvent::Window(event) => Delivery::Window(event),
+        }
+    }
+}
+
+impl From<InputEvent> for SyntheticEvent {
+    fn from(input: InputEvent) -> Self {
+        SyntheticEvent::Input(input)
+    }
+}
+
+impl From<WindowEvent> for SyntheticEvent {
+    fn from(event: WindowEvent) -> Self {
+        SyntheticEvent::Window(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use skia_safe::Point;
+    use winit::dpi::PhysicalSize;
+    use winit::keyboard::KeyCode;
+    use crate::event::gesture::GestureEvent;
+    use crate::event::input::{KeyboardEvent, Modifiers};
+    use crate::event::pipeline::InputPipeline;
+    use crate::event::ui_event::UIEvent;
+    use super::*;
+
+    /// Delivers the events a millisecond apart, as `Application` would over a few loop iterations.
+    fn deliver(events: Vec<SyntheticEvent>) -> Vec<UIEvent> {
+        let mut pipeline = InputPipeline::with_double_click_time(Duration::from_millis(400));
+        let start = Instant::now();
+        events.into_iter()
+            .enumerate()
+            .flat_map(|(index, event)| match event.into_delivery(1.0) {
+                Delivery::Input(input) => pipeline.process(&input, start + Duration::from_millis(index as u64), &[]),
+                Delivery::Window(_) => panic!("not input"),
+            })
+            .collect()
+    }
+
+    fn key(key: &Key, physical_key: PhysicalKey) -> KeyboardEvent {
+        KeyboardEvent {
+            key: key.clone(),
+            physical_key,
+            location: KeyLocation::Standard,
+            repeat: false,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[test]
+    fn click_moves_presses_and_releases() {
+        let events = deliver(SyntheticEvent::click(10.0, 20.0, PointerButton::Primary));
+        let position = Point::new(10.0, 20.0);
+        assert_eq!(events.len(), 4, "{:?}", events);
+        assert!(matches!(&events[0], UIEvent::PointerMove(pointer) if pointer.position == position));
+        assert!(matches!(
+            &events[1],
+            UIEvent::PointerDown(pointer)
+                if pointer.position == position && pointer.button == Some(PointerButton::Primary) && pointer.click_count == 1
+        ));
+        assert!(matches!(&events[2], UIEvent::PointerUp(pointer) if pointer.button == Some(PointerButton::Primary)));
+        assert_eq!(events[3], UIEvent::Gesture(GestureEvent::Tap { position, count: 1 }));
+    }
+
+    #[test]
+    fn key_press_types_its_text_once() {
+        let q = Key::Character(SmolStr::new("q"));
+        let physical_key = PhysicalKey::Code(KeyCode::KeyQ);
+        let events = deliver(SyntheticEvent::key_press(q.clone(), physical_key, Some("q")));
+        assert_eq!(events, vec![
+            UIEvent::KeyDown(key(&q, physical_key)),
+            UIEvent::TextInput("q".to_string()),
+            UIEvent::KeyUp(key(&q, physical_key)),
+        ]);
+    }
+
+    #[test]
+    fn type_text_types_every_character() {
+        let events = deliver(SyntheticEvent::type_text("hé!"));
+        let expected: Vec<UIEvent> = ["h", "é", "!"].into_iter()
+            .flat_map(|text| {
+                let typed = Key::Character(SmolStr::new(text));
+                let physical_key = PhysicalKey::Unidentified(NativeKeyCode::Unidentified);
+                [
+                    UIEvent::KeyDown(key(&typed, physical_key)),
+                    UIEvent::TextInput(text.to_string()),
+                    UIEvent::KeyUp(key(&typed, physical_key)),
+                ]
+            })
+            .collect();
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn scroll_happens_under_the_cursor() {
+        let events = deliver(SyntheticEvent::scroll(5.0, 6.0, WheelDelta::Lines(0.0, 3.0)));
+        assert_eq!(events.len(), 2, "{:?}", events);
+        assert!(matches!(&events[0], UIEvent::PointerMove(pointer) if pointer.position == Point::new(5.0, 6.0)));
+        assert!(matches!(
+            &events[1],
+            UIEvent::Wheel(wheel) if wheel.position == Point::new(5.0, 6.0) && wheel.delta == WheelDelta::Lines(0.0, 3.0)
+        ));
+    }
+
+    #[test]
+    fn resize_is_logical() {
+        let Delivery::Window(WindowEvent::Resized(size)) = SyntheticEvent::resize(300.0, 200.0).into_delivery(2.0) else {
+            panic!("not a resize");
+        };
+        assert_eq!(size, PhysicalSize::new(600, 400));
+        assert_eq!(size.to_logical::<f32>(2.0), LogicalSize::new(300.0, 200.0));
+    }
+
+    #[test]
+    fn focus_is_a_window_event() {
+        assert!(matches!(SyntheticEvent::Focus(true).into_delivery(1.0), Delivery::Window(WindowEvent::Focused(true))));
+    }
+}
@@ -1,9 +1,121 @@
+use std::any::Any;
+use skia_safe::Size;
 use winit::event::WindowEvent;
+use winit::window::WindowId;
 use crate::application::TimerId;
+use crate::event::gesture::GestureEvent;
+use crate::event::input::{FileDragEvent, KeyboardEvent, PointerEvent, WheelEvent};
+use crate::event::window_message::WindowMessage;
+#[cfg(feature = "gamepad")]
+use crate::gamepad::GamepadEvent;
 
 pub enum UIEvent {
     TimerOut(TimerId),
     Interval(TimerId),
+    /// An animation started with `WindowContext::animate` has completed.
+    AnimationEnd(TimerId),
     WindowCreate,
+    /// The user asked to close the window. Call `WindowContext::prevent_close` to keep it open.
+    CloseRequested,
+    FocusGained,
+    FocusLost,
+    /// The window entered (`true`) or left (`false`) fullscreen, by the app or by the platform.
+    FullscreenChange(bool),
+    /// The window is being destroyed. Sent once, after the close can no longer be prevented.
+    WindowClose,
+    /// A modal window opened with `Context::new_modal_window` on this window has closed,
+    /// with the value it passed to `WindowContext::close_with_result`, if any.
+    ModalClose(WindowId, Option<Box<dyn Any>>),
+    Message(WindowMessage),
+    PointerDown(PointerEvent),
+    PointerUp(PointerEvent),
+    PointerMove(PointerEvent),
+    PointerEnter(PointerEvent),
+    PointerLeave(PointerEvent),
+    /// The platform took over the pointer, e.g. for a system gesture. No `PointerUp` follows.
+    PointerCancel(PointerEvent),
+    Wheel(WheelEvent),
+    /// Follows the pointer or touchpad events it was recognized from.
+    Gesture(GestureEvent),
+    KeyDown(KeyboardEvent),
+    KeyUp(KeyboardEvent),
+    /// Text typed by the user, follows the `KeyDown` that produced it.
+    TextInput(String),
+    /// The input method became active, after `WindowControl::set_ime_allowed`.
+    ImeEnabled,
+    /// Text being composed by the input method, to show in place of the caret until it is committed.
+    /// The range is the byte range of the cursor in the text, hide the caret if `None`.
+    /// An empty text clears the composition.
+    ImePreedit(String, Option<(usize, usize)>),
+    /// Text the input method is done composing, insert it like `TextInput`.
+    ImeCommit(String),
+    ImeDisabled,
+    /// The shortcut of a command registered with `Context::commands` or `WindowContext::commands` was pressed.
+    /// The keys of the shortcut aren't delivered as `KeyDown` or `TextInput`.
+    Command(String),
+    /// New size of the client area in logical pixels.
+    Resize(Size),
+    ScaleFactorChange(f64),
+    /// Files are dragged into the window. Call `WindowContext::accept_drop` to receive them in `FileDrop`.
+    FileDragEnter(FileDragEvent),
+    /// Follows `FileDragEnter` and every file reported later, then comes on every cursor move platforms
+    /// report during the drag, which most don't. Calling `WindowContext::accept_drop` or not replaces the previous choice.
+    FileDragOver(FileDragEvent),
+    /// The drag left the window, was cancelled, or the files were dropped without being accepted.
+    FileDragLeave,
+    FileDrop(FileDragEvent),
+    /// Input of a gamepad or joystick, delivered to the focused window only.
+    #[cfg(feature = "gamepad")]
+    Gamepad(GamepadEvent),
+    /// Window events without a typed variant.
     Other(WindowEvent)
-}
\ No newline at end of file
+}
+/// Input variants compare by their payload, so tests can check what input turns into.
+/// Other variants, such as messages and modal results carrying arbitrary values, never compare equal.
+#[cfg(test)]
+impl PartialEq for UIEvent {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (UIEvent::PointerDown(a), UIEvent::PointerDown(b))
+            | (UIEvent::PointerUp(a), UIEvent::PointerUp(b))
+            | (UIEvent::PointerMove(a), UIEvent::PointerMove(b))
+            | (UIEvent::PointerEnter(a), UIEvent::PointerEnter(b))
+            | (UIEvent::PointerLeave(a), UIEvent::PointerLeave(b))
+            | (UIEvent::PointerCancel(a), UIEvent::PointerCancel(b)) => a == b,
+            (UIEvent::Wheel(a), UIEvent::Wheel(b)) => a == b,
+            (UIEvent::Gesture(a), UIEvent::Gesture(b)) => a == b,
+            (UIEvent::KeyDown(a), UIEvent::KeyDown(b)) | (UIEvent::KeyUp(a), UIEvent::KeyUp(b)) => a == b,
+            (UIEvent::TextInput(a), UIEvent::TextInput(b))
+            | (UIEvent::ImeCommit(a), UIEvent::ImeCommit(b))
+            | (UIEvent::Command(a), UIEvent::Command(b)) => a == b,
+            (UIEvent::ImePreedit(a, a_cursor), UIEvent::ImePreedit(b, b_cursor)) => a == b && a_cursor == b_cursor,
+            (UIEvent::ImeEnabled, UIEvent::ImeEnabled) | (UIEvent::ImeDisabled, UIEvent::ImeDisabled) => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+impl std::fmt::Debug for UIEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UIEvent::PointerDown(pointer) => f.debug_tuple("PointerDown").field(pointer).finish(),
+            UIEvent::PointerUp(pointer) => f.debug_tuple("PointerUp").field(pointer).finish(),
+            UIEvent::PointerMove(pointer) => f.debug_tuple("PointerMove").field(pointer).finish(),
+            UIEvent::PointerEnter(pointer) => f.debug_tuple("PointerEnter").field(pointer).finish(),
+            UIEvent::PointerLeave(pointer) => f.debug_tuple("PointerLeave").field(pointer).finish(),
+            UIEvent::PointerCancel(pointer) => f.debug_tuple("PointerCancel").field(pointer).finish(),
+            UIEvent::Wheel(wheel) => f.debug_tuple("Wheel").field(wheel).finish(),
+            UIEvent::Gesture(gesture) => f.debug_tuple("Gesture").field(gesture).finish(),
+            UIEvent::KeyDown(key) => f.debug_tuple("KeyDown").field(key).finish(),
+            UIEvent::KeyUp(key) => f.debug_tuple("KeyUp").field(key).finish(),
+            UIEvent::TextInput(text) => f.debug_tuple("TextInput").field(text).finish(),
+            UIEvent::ImeEnabled => f.write_str("ImeEnabled"),
+            UIEvent::ImePreedit(text, cursor) => f.debug_tuple("ImePreedit").field(text).field(cursor).finish(),
+            UIEvent::ImeCommit(text) => f.debug_tuple("ImeCommit").field(text).finish(),
+            UIEvent::ImeDisabled => f.write_str("ImeDisabled"),
+            UIEvent::Command(command) => f.debug_tuple("Command").field(command).finish(),
+            _ => f.write_str("UIEvent { .. }"),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::any::Any;
+use std::rc::Rc;
+use winit::window::WindowId;
+
+/// A value sent to a window with `Context::send_message` and friends, delivered as `UIEvent::Message`.
+#[derive(Clone)]
+pub struct WindowMessage {
+    sender: Option<WindowId>,
+    payload: Rc<dyn Any>,
+}
+
+impl WindowMessage {
+    pub(crate) fn new(sender: Option<WindowId>, payload: impl Any) -> Self {
+        Self {
+            sender,
+            payload: Rc::new(payload),
+        }
+    }
+
+    /// The sending window, `None` if the message was sent by the application.
+    pub fn sender(&self) -> Option<WindowId> {
+        self.sender
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.payload.is::<T>()
+    }
+
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.payload.downcast_ref()
+    }
+}
@@ -1,14 +1,46 @@
+use std::collections::HashMap;
+use winit::event::{DeviceEvent, DeviceId};
 use crate::context::context::Context;
+use crate::single_instance::InstanceArgs;
+use crate::ui::UI;
+use crate::window_config::{WindowConfigError, WindowConfigFile};
 
+type Handler<T = ()> = Option<Box<dyn Fn(&mut Context) -> T>>;
+type ArgHandler<A> = Option<Box<dyn Fn(&mut Context, A)>>;
+type DeviceEventHandler = Option<Box<dyn Fn(&mut Context, DeviceId, DeviceEvent)>>;
 
 pub struct EventHandler {
-    handle_init: Option<Box<dyn Fn(&mut Context)>>,
+    handle_init: Handler,
+    handle_resumed: Handler,
+    handle_suspended: Handler,
+    handle_about_to_wait: Handler,
+    handle_exit_requested: Handler<bool>,
+    handle_loop_exiting: Handler,
+    handle_last_window_closed: Handler,
+    handle_memory_warning: Handler,
+    handle_device_event: DeviceEventHandler,
+    handle_second_instance: ArgHandler<InstanceArgs>,
+    handle_window_config_error: ArgHandler<WindowConfigError>,
+    ui_factory_map: HashMap<String, Box<dyn Fn() -> Box<dyn UI>>>,
+    window_config: Option<WindowConfigFile>,
 }
 
 impl EventHandler {
     pub fn new() -> Self {
         Self {
-            handle_init: None
+            handle_init: None,
+            handle_resumed: None,
+            handle_suspended: None,
+            handle_about_to_wait: None,
+            handle_exit_requested: None,
+            handle_loop_exiting: None,
+            handle_last_window_closed: None,
+            handle_memory_warning: None,
+            handle_device_event: None,
+            handle_second_instance: None,
+            handle_window_config_error: None,
+            ui_factory_map: HashMap::new(),
+            window_config: None,
         }
     }
 
@@ -18,11 +50,171 @@ impl EventHandler {
         )
     }
 
+    pub fn add_resumed_handler(&mut self, handle_resumed: impl Fn(&mut Context) + 'static) {
+        self.handle_resumed = Some(
+            Box::new(handle_resumed)
+        )
+    }
+
+    pub fn add_suspended_handler(&mut self, handle_suspended: impl Fn(&mut Context) + 'static) {
+        self.handle_suspended = Some(
+            Box::new(handle_suspended)
+        )
+    }
+
+    /// Runs once per event loop iteration, after all pending events have been handled.
+    pub fn add_about_to_wait_handler(&mut self, handle_about_to_wait: impl Fn(&mut Context) + 'static) {
+        self.handle_about_to_wait = Some(
+            Box::new(handle_about_to_wait)
+        )
+    }
+
+    /// Runs when `Context::exit` is called, which includes the last window being closed unless a last window
+    /// closed handler is set. Returning `false` cancels the exit as long as a window is open: an app without
+    /// windows that cancelled its exit would keep running with no way for the user to quit it, so it exits anyway.
+    /// A handler can keep the app running by opening a window, e.g. to ask whether to save changes.
+    pub fn add_exit_requested_handler(&mut self, handle_exit_requested: impl Fn(&mut Context) -> bool + 'static) {
+        self.handle_exit_requested = Some(
+            Box::new(handle_exit_requested)
+        )
+    }
+
+    pub fn add_loop_exiting_handler(&mut self, handle_loop_exiting: impl Fn(&mut Context) + 'static) {
+        self.handle_loop_exiting = Some(
+            Box::new(handle_loop_exiting)
+        )
+    }
+
+    /// Replaces the default behavior of exiting when the last window is closed, e.g. to keep running in the tray.
+    /// It doesn't run for the windows closed by an exit.
+    pub fn add_last_window_closed_handler(&mut self, handle_last_window_closed: impl Fn(&mut Context) + 'static) {
+        self.handle_last_window_closed = Some(
+            Box::new(handle_last_window_closed)
+        )
+    }
+
+    pub fn add_memory_warning_handler(&mut self, handle_memory_warning: impl Fn(&mut Context) + 'static) {
+        self.handle_memory_warning = Some(
+            Box::new(handle_memory_warning)
+        )
+    }
+
+    pub fn add_device_event_handler(&mut self, handle_device_event: impl Fn(&mut Context, DeviceId, DeviceEvent) + 'static) {
+        self.handle_device_event = Some(
+            Box::new(handle_device_event)
+        )
+    }
+
+    /// Runs in the first instance when another one is launched by `launch_single_instance`,
+    /// typically to open the forwarded files or focus an existing window.
+    pub fn add_second_instance_handler(&mut self, handle_second_instance: impl Fn(&mut Context, InstanceArgs) + 'static) {
+        self.handle_second_instance = Some(
+            Box::new(handle_second_instance)
+        )
+    }
+
+    /// Runs for every window of the window config that can't be opened, which is then skipped.
+    /// Without this handler, the error is printed to stderr.
+    pub fn add_window_config_error_handler(&mut self, handle_window_config_error: impl Fn(&mut Context, WindowConfigError) + 'static) {
+        self.handle_window_config_error = Some(
+            Box::new(handle_window_config_error)
+        )
+    }
+
+    /// Makes `factory` available to window configs under `name`.
+    pub fn register_ui<U: UI + 'static>(&mut self, name: impl Into<String>, factory: impl Fn() -> U + 'static) {
+        self.ui_factory_map.insert(name.into(), Box::new(move || Box::new(factory())));
+    }
+
+    /// Windows to open at launch, right after the init handler has run. Windows that can't be opened
+    /// go to the window config error handler.
+    pub fn set_window_config(&mut self, window_config: WindowConfigFile) {
+        self.window_config = Some(window_config);
+    }
+
     pub(crate) fn on_init(&self, event_context: &mut Context) {
         if let Some(handler) = &self.handle_init {
             handler(event_context);
         }
     }
+
+    pub(crate) fn open_configured_windows(&self, event_context: &mut Context) {
+        let Some(window_config) = &self.window_config else {
+            return;
+        };
+        for window in window_config.resolve(&self.ui_factory_map) {
+            match window {
+                Ok((config, factory)) => {
+                    event_context.new_window_from_config(config, factory());
+                }
+                Err(error) => self.on_window_config_error(event_context, error),
+            }
+        }
+    }
+
+    fn on_window_config_error(&self, event_context: &mut Context, error: WindowConfigError) {
+        match &self.handle_window_config_error {
+            Some(handler) => handler(event_context, error),
+            None => eprintln!("{}", error),
+        }
+    }
+
+    pub(crate) fn on_resumed(&self, event_context: &mut Context) {
+        if let Some(handler) = &self.handle_resumed {
+            handler(event_context);
+        }
+    }
+
+    pub(crate) fn on_suspended(&self, event_context: &mut Context) {
+        if let Some(handler) = &self.handle_suspended {
+            handler(event_context);
+        }
+    }
+
+    pub(crate) fn on_about_to_wait(&self, event_context: &mut Context) {
+        if let Some(handler) = &self.handle_about_to_wait {
+            handler(event_context);
+        }
+    }
+
+    pub(crate) fn on_exit_requested(&self, event_context: &mut Context) -> bool {
+        if let Some(handler) = &self.handle_exit_requested {
+            return handler(event_context);
+        }
+        true
+    }
+
+    pub(crate) fn on_loop_exiting(&self, event_context: &mut Context) {
+        if let Some(handler) = &self.handle_loop_exiting {
+            handler(event_context);
+        }
+    }
+
+    pub(crate) fn on_last_window_closed(&self, event_context: &mut Context) {
+        if let Some(handler) = &self.handle_last_window_closed {
+            handler(event_context);
+        } else {
+            event_context.exit();
+        }
+    }
+
+    pub(crate) fn on_memory_warning(&self, event_context: &mut Context) {
+        if let Some(handler) = &self.handle_memory_warning {
+            handler(event_context);
+        }
+    }
+
+    pub(crate) fn on_device_event(&self, event_context: &mut Context, device_id: DeviceId, event: DeviceEvent) {
+        if let Some(handler) = &self.handle_device_event {
+            handler(event_context, device_id, event);
+        }
+    }
+
+    pub(crate) fn on_second_instance(&self, event_context: &mut Context, args: InstanceArgs) {
+        if let Some(handler) = &self.handle_second_instance {
+            handler(event_context, args);
+        }
+    }
 }
 
 impl Default for EventHandler {
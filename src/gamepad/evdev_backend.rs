@@ -0,0 +1,324 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use evdev::{AbsoluteAxisType, Device, InputEventKind, Key};
+use crate::gamepad::{GamepadAxis, GamepadBackend, GamepadButton, GamepadEvent, GamepadId, GamepadWaker};
+
+const INPUT_DIR: &str = "/dev/input";
+/// How often `/dev/input` is looked through for controllers plugged in since.
+const SCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+struct EvdevGamepad {
+    id: GamepadId,
+    device: Device,
+    /// Minimum and maximum of every absolute axis, by axis code.
+    axis_ranges: HashMap<u16, (i32, i32)>,
+}
+
+/// Reads gamepads and joysticks from `/dev/input/event*`, which usually requires the user to be
+/// in the `input` group. Virtual devices created with uinput show up like real ones.
+/// The devices are read on a thread of their own from `start` on, which wakes the event loop up.
+/// Dropping the backend stops the thread and waits for it to close the devices.
+pub struct EvdevBackend {
+    receiver: Option<Receiver<GamepadEvent>>,
+    thread: Option<ReaderThread>,
+}
+
+/// The running reader thread and the eventfd that interrupts its wait to stop it.
+struct ReaderThread {
+    stop: Arc<OwnedFd>,
+    handle: JoinHandle<()>,
+}
+
+impl EvdevBackend {
+    pub fn new() -> Self {
+        Self {
+            receiver: None,
+            thread: None,
+        }
+    }
+}
+
+impl Default for EvdevBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for EvdevBackend {
+    fn drop(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        let value: u64 = 1;
+        let written = unsafe { libc::write(thread.stop.as_raw_fd(), &value as *const u64 as *const libc::c_void, 8) };
+        // Without the signal the thread would never return, it is left running with its own handle on the eventfd.
+        if written == 8 {
+            let _ = thread.handle.join();
+        }
+    }
+}
+
+impl GamepadBackend for EvdevBackend {
+    fn start(&mut self, waker: GamepadWaker) {
+        if self.receiver.is_some() {
+            return;
+        }
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC) };
+        if fd < 0 {
+            eprintln!("gamepads unavailable: {}", io::Error::last_os_error());
+            return;
+        }
+        let stop = Arc::new(unsafe { OwnedFd::from_raw_fd(fd) });
+        let (sender, receiver) = mpsc::channel();
+        self.receiver = Some(receiver);
+        let thread_stop = stop.clone();
+        let handle = thread::spawn(move || {
+            let mut reader = Reader::new();
+            loop {
+                let events = reader.read(Instant::now());
+                if !events.is_empty() {
+                    for event in events {
+                        if sender.send(event).is_err() {
+                            return;
+                        }
+                    }
+                    waker();
+                }
+                if reader.wait(thread_stop.as_raw_fd()) {
+                    return;
+                }
+            }
+        });
+        self.thread = Some(ReaderThread { stop, handle });
+    }
+
+    fn poll(&mut self, _now: Instant) -> Vec<GamepadEvent> {
+        match &self.receiver {
+            Some(receiver) => receiver.try_iter().collect(),
+            None => vec![],
+        }
+    }
+}
+
+/// The devices, owned by the reader thread.
+struct Reader {
+    gamepads: HashMap<PathBuf, EvdevGamepad>,
+    /// Devices that aren't controllers, so they aren't opened again on every scan.
+    ignored: HashSet<PathBuf>,
+    last_id: GamepadId,
+    scanned_at: Option<Instant>,
+}
+
+impl Reader {
+    fn new() -> Self {
+        Self {
+            gamepads: HashMap::new(),
+            ignored: HashSet::new(),
+            last_id: GamepadId::default(),
+            scanned_at: None,
+        }
+    }
+
+    /// Blocks until a device has input or was unplugged, the next scan is due, or `stop` is signalled.
+    /// Returns whether the thread has to stop.
+    fn wait(&self, stop: RawFd) -> bool {
+        let mut fds: Vec<libc::pollfd> = std::iter::once(stop)
+            .chain(self.gamepads.values().map(|gamepad| gamepad.device.as_raw_fd()))
+            .map(|fd| libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            })
+            .collect();
+        let timeout = match self.scanned_at {
+            Some(scanned_at) => SCAN_INTERVAL.saturating_sub(scanned_at.elapsed()),
+            None => Duration::ZERO,
+        };
+        // Interruptions and errors only make the thread read and scan earlier.
+        unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout.as_millis() as libc::c_int) };
+        fds[0].revents & libc::POLLIN != 0
+    }
+
+    fn scan(&mut self, events: &mut Vec<GamepadEvent>) {
+        let Ok(entries) = fs::read_dir(INPUT_DIR) else {
+            return;
+        };
+        let paths: HashSet<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with("event")))
+            .collect();
+        self.ignored.retain(|path| paths.contains(path));
+        for path in paths {
+            if self.gamepads.contains_key(&path) || self.ignored.contains(&path) {
+                continue;
+            }
+            // Devices that can't be opened are tried again on the next scan, permissions are often
+            // granted a moment after the device appears.
+            match open_gamepad(&path) {
+                Ok(Some(device)) => {
+                    let id = self.last_id.next();
+                    self.last_id = id;
+                    events.push(GamepadEvent::Connected {
+                        id,
+                        name: device.name().unwrap_or("Gamepad").to_string(),
+                    });
+                    let axis_ranges = axis_ranges(&device);
+                    self.gamepads.insert(path, EvdevGamepad { id, device, axis_ranges });
+                }
+                Ok(None) => {
+                    self.ignored.insert(path);
+                }
+                Err(_) => {}
+            }
+        }
+    }
+
+    fn read(&mut self, now: Instant) -> Vec<GamepadEvent> {
+        let mut events = vec![];
+        let scan_due = match self.scanned_at {
+            Some(scanned_at) => now.saturating_duration_since(scanned_at) >= SCAN_INTERVAL,
+            None => true,
+        };
+        if scan_due {
+            self.scanned_at = Some(now);
+            self.scan(&mut events);
+        }
+        let mut disconnected = vec![];
+        for (path, gamepad) in &mut self.gamepads {
+            let EvdevGamepad { id, device, axis_ranges } = gamepad;
+            match device.fetch_events() {
+                Ok(input_events) => {
+                    for input_event in input_events {
+                        match input_event.kind() {
+                            InputEventKind::Key(key) => {
+                                let button = button(key);
+                                match input_event.value() {
+                                    0 => events.push(GamepadEvent::ButtonReleased { id: *id, button }),
+                                    1 => events.push(GamepadEvent::ButtonPressed { id: *id, button }),
+                                    // Key repeat.
+                                    _ => {}
+                                }
+                            }
+                            InputEventKind::AbsAxis(axis_type) => {
+                                let axis = axis(axis_type);
+                                let range = axis_ranges.get(&axis_type.0).copied();
+                                events.push(GamepadEvent::AxisChanged {
+                                    id: *id,
+                                    axis,
+                                    value: normalize(axis, input_event.value(), range),
+                                });
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {}
+                Err(_) => disconnected.push(path.clone()),
+            }
+        }
+        for path in disconnected {
+            if let Some(gamepad) = self.gamepads.remove(&path) {
+                events.push(GamepadEvent::Disconnected { id: gamepad.id });
+            }
+        }
+        events
+    }
+}
+
+/// Opens the device if it is a gamepad or joystick.
+fn open_gamepad(path: &Path) -> io::Result<Option<Device>> {
+    let device = Device::open(path)?;
+    let is_gamepad = device.supported_keys().is_some_and(|keys| keys.contains(Key::BTN_SOUTH) || keys.contains(Key::BTN_TRIGGER));
+    if !is_gamepad {
+        return Ok(None);
+    }
+    // Every device is read once any of them has input, reads must not wait.
+    if unsafe { libc::fcntl(device.as_raw_fd(), libc::F_SETFL, libc::O_NONBLOCK) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(Some(device))
+}
+
+fn axis_ranges(device: &Device) -> HashMap<u16, (i32, i32)> {
+    let (Some(axes), Ok(state)) = (device.supported_absolute_axes(), device.get_abs_state()) else {
+        return HashMap::new();
+    };
+    axes.iter()
+        .map(|axis| {
+            let info = state[axis.0 as usize];
+            (axis.0, (info.minimum, info.maximum))
+        })
+        .collect()
+}
+
+fn normalize(axis: GamepadAxis, value: i32, range: Option<(i32, i32)>) -> f32 {
+    let Some((minimum, maximum)) = range.filter(|(minimum, maximum)| maximum > minimum) else {
+        return value as f32;
+    };
+    let value = (value - minimum) as f32 / (maximum - minimum) as f32;
+    match axis {
+        GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger => value,
+        _ => value * 2.0 - 1.0,
+    }
+}
+
+fn button(key: Key) -> GamepadButton {
+    match key {
+        Key::BTN_SOUTH => GamepadButton::South,
+        Key::BTN_EAST => GamepadButton::East,
+        Key::BTN_NORTH => GamepadButton::North,
+        Key::BTN_WEST => GamepadButton::West,
+        Key::BTN_TL => GamepadButton::LeftBumper,
+        Key::BTN_TR => GamepadButton::RightBumper,
+        Key::BTN_TL2 => GamepadButton::LeftTrigger,
+        Key::BTN_TR2 => GamepadButton::RightTrigger,
+        Key::BTN_SELECT => GamepadButton::Select,
+        Key::BTN_START => GamepadButton::Start,
+        Key::BTN_MODE => GamepadButton::Mode,
+        Key::BTN_THUMBL => GamepadButton::LeftStick,
+        Key::BTN_THUMBR => GamepadButton::RightStick,
+        Key::BTN_DPAD_UP => GamepadButton::DPadUp,
+        Key::BTN_DPAD_DOWN => GamepadButton::DPadDown,
+        Key::BTN_DPAD_LEFT => GamepadButton::DPadLeft,
+        Key::BTN_DPAD_RIGHT => GamepadButton::DPadRight,
+        key => GamepadButton::Other(key.code()),
+    }
+}
+
+fn axis(axis: AbsoluteAxisType) -> GamepadAxis {
+    match axis {
+        AbsoluteAxisType::ABS_X => GamepadAxis::LeftStickX,
+        AbsoluteAxisType::ABS_Y => GamepadAxis::LeftStickY,
+        AbsoluteAxisType::ABS_RX => GamepadAxis::RightStickX,
+        AbsoluteAxisType::ABS_RY => GamepadAxis::RightStickY,
+        AbsoluteAxisType::ABS_Z => GamepadAxis::LeftTrigger,
+        AbsoluteAxisType::ABS_RZ => GamepadAxis::RightTrigger,
+        AbsoluteAxisType::ABS_HAT0X => GamepadAxis::DPadX,
+        AbsoluteAxisType::ABS_HAT0Y => GamepadAxis::DPadY,
+        axis => GamepadAxis::Other(axis.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropping_stops_the_reader_thread_right_away() {
+        let mut backend = EvdevBackend::new();
+        backend.start(Box::new(|| {}));
+        // Past the first scan, into the wait for the next one.
+        thread::sleep(Duration::from_millis(50));
+        let dropped_at = Instant::now();
+        drop(backend);
+        assert!(dropped_at.elapsed() < SCAN_INTERVAL / 4, "{:?}", dropped_at.elapsed());
+    }
+}
@@ -0,0 +1,312 @@
+#[cfg(target_os = "linux")]
+mod evdev_backend;
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::Instant;
+use winit::window::WindowId;
+
+#[cfg(target_os = "linux")]
+pub use evdev_backend::EvdevBackend;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct GamepadId(u32);
+
+impl GamepadId {
+    pub fn next(&self) -> Self {
+        Self(self.0 + 1)
+    }
+}
+
+/// Buttons named after their position, whatever their label on the controller.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    /// A on Xbox controllers, Cross on PlayStation ones.
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    /// Only sent by controllers with digital triggers, analog ones move `GamepadAxis::LeftTrigger`.
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    /// The button with the vendor logo.
+    Mode,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    /// Any other button, e.g. of a joystick, by its platform code.
+    Other(u16),
+}
+
+/// Sticks and the d-pad range from `-1` to `1`, positive being right and down. Triggers range from `0` to `1`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+    DPadX,
+    DPadY,
+    /// Any other axis, e.g. the throttle of a joystick, by its platform code.
+    Other(u16),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GamepadEvent {
+    Connected {
+        id: GamepadId,
+        name: String,
+    },
+    Disconnected {
+        id: GamepadId,
+    },
+    ButtonPressed {
+        id: GamepadId,
+        button: GamepadButton,
+    },
+    ButtonReleased {
+        id: GamepadId,
+        button: GamepadButton,
+    },
+    AxisChanged {
+        id: GamepadId,
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+impl GamepadEvent {
+    pub fn id(&self) -> GamepadId {
+        match self {
+            GamepadEvent::Connected { id, .. }
+            | GamepadEvent::Disconnected { id }
+            | GamepadEvent::ButtonPressed { id, .. }
+            | GamepadEvent::ButtonReleased { id, .. }
+            | GamepadEvent::AxisChanged { id, .. } => *id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gamepad {
+    pub id: GamepadId,
+    pub name: String,
+}
+
+/// Wakes the event loop up, callable from any thread.
+pub type GamepadWaker = Box<dyn Fn() + Send>;
+
+/// Where gamepad events come from, polled on every event loop iteration.
+/// Set with `Context::set_gamepad_backend`, the default one reads evdev devices on Linux.
+pub trait GamepadBackend {
+    /// Called once the backend is set. The event loop sleeps while nothing happens, so backends
+    /// call `waker` when events are ready to be polled.
+    fn start(&mut self, _waker: GamepadWaker) {}
+
+    fn poll(&mut self, now: Instant) -> Vec<GamepadEvent>;
+}
+
+/// The backend used unless another one is set.
+#[cfg(target_os = "linux")]
+pub(crate) fn default_backend() -> Option<Box<dyn GamepadBackend>> {
+    Some(Box::new(EvdevBackend::new()))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn default_backend() -> Option<Box<dyn GamepadBackend>> {
+    None
+}
+
+/// Keeps track of the connected gamepads and routes their events to the focused window.
+#[derive(Default)]
+pub(crate) struct GamepadRouter {
+    backend: Option<Box<dyn GamepadBackend>>,
+    gamepads: Vec<Gamepad>,
+}
+
+impl GamepadRouter {
+    /// Gamepads of the previous backend are forgotten without a `Disconnected` event.
+    pub fn set_backend(&mut self, mut backend: Box<dyn GamepadBackend>, waker: GamepadWaker) {
+        backend.start(waker);
+        self.backend = Some(backend);
+        self.gamepads.clear();
+    }
+
+    pub fn gamepads(&self) -> &[Gamepad] {
+        &self.gamepads
+    }
+
+    /// Polls the backend and returns the events with the window they go to. Events arriving while
+    /// none of our windows has the focus still update the connected gamepads, but are dropped.
+    pub fn poll(&mut self, now: Instant, focused_window: Option<WindowId>) -> Vec<(WindowId, GamepadEvent)> {
+        let Some(backend) = self.backend.as_mut() else {
+            return vec![];
+        };
+        let mut routed = vec![];
+        for event in backend.poll(now) {
+            match &event {
+                GamepadEvent::Connected { id, name } => self.gamepads.push(Gamepad {
+                    id: *id,
+                    name: name.clone(),
+                }),
+                GamepadEvent::Disconnected { id } => self.gamepads.retain(|gamepad| gamepad.id != *id),
+                _ => {}
+            }
+            if let Some(window_id) = focused_window {
+                routed.push((window_id, event));
+            }
+        }
+        routed
+    }
+}
+
+/// A backend fed by hand, for tests. Clones share their events: keep one to drive the gamepads and
+/// hand another to `Context::set_gamepad_backend`.
+#[derive(Clone, Default)]
+pub struct MockGamepadBackend {
+    events: Rc<RefCell<VecDeque<GamepadEvent>>>,
+    last_id: Rc<Cell<GamepadId>>,
+    waker: Rc<RefCell<Option<GamepadWaker>>>,
+}
+
+impl MockGamepadBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connects a new gamepad and returns its id.
+    pub fn connect(&self, name: impl Into<String>) -> GamepadId {
+        let id = self.last_id.get().next();
+        self.last_id.set(id);
+        self.send(GamepadEvent::Connected {
+            id,
+            name: name.into(),
+        });
+        id
+    }
+
+    pub fn disconnect(&self, id: GamepadId) {
+        self.send(GamepadEvent::Disconnected { id });
+    }
+
+    pub fn press(&self, id: GamepadId, button: GamepadButton) {
+        self.send(GamepadEvent::ButtonPressed { id, button });
+    }
+
+    pub fn release(&self, id: GamepadId, button: GamepadButton) {
+        self.send(GamepadEvent::ButtonReleased { id, button });
+    }
+
+    pub fn move_axis(&self, id: GamepadId, axis: GamepadAxis, value: f32) {
+        self.send(GamepadEvent::AxisChanged { id, axis, value });
+    }
+
+    /// Queues the event and wakes the event loop up to poll it.
+    pub fn send(&self, event: GamepadEvent) {
+        self.events.borrow_mut().push_back(event);
+        if let Some(waker) = self.waker.borrow().as_ref() {
+            waker();
+        }
+    }
+}
+
+impl GamepadBackend for MockGamepadBackend {
+    fn start(&mut self, waker: GamepadWaker) {
+        *self.waker.borrow_mut() = Some(waker);
+    }
+
+    fn poll(&mut self, _now: Instant) -> Vec<GamepadEvent> {
+        self.events.borrow_mut().drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use super::*;
+
+    fn window_id() -> WindowId {
+        unsafe { WindowId::dummy() }
+    }
+
+    fn router(backend: &MockGamepadBackend) -> GamepadRouter {
+        let mut router = GamepadRouter::default();
+        router.set_backend(Box::new(backend.clone()), Box::new(|| {}));
+        router
+    }
+
+    #[test]
+    fn events_go_to_the_focused_window() {
+        let backend = MockGamepadBackend::new();
+        let mut router = router(&backend);
+        let id = backend.connect("Pad");
+        backend.press(id, GamepadButton::South);
+        backend.release(id, GamepadButton::South);
+        backend.move_axis(id, GamepadAxis::LeftStickX, -0.5);
+        backend.disconnect(id);
+        let routed = router.poll(Instant::now(), Some(window_id()));
+        assert!(routed.iter().all(|(target, _)| *target == window_id()));
+        let events: Vec<GamepadEvent> = routed.into_iter().map(|(_, event)| event).collect();
+        assert_eq!(events, vec![
+            GamepadEvent::Connected { id, name: "Pad".to_string() },
+            GamepadEvent::ButtonPressed { id, button: GamepadButton::South },
+            GamepadEvent::ButtonReleased { id, button: GamepadButton::South },
+            GamepadEvent::AxisChanged { id, axis: GamepadAxis::LeftStickX, value: -0.5 },
+            GamepadEvent::Disconnected { id },
+        ]);
+    }
+
+    #[test]
+    fn connected_gamepads_are_tracked() {
+        let backend = MockGamepadBackend::new();
+        let mut router = router(&backend);
+        let first = backend.connect("First");
+        let second = backend.connect("Second");
+        router.poll(Instant::now(), Some(window_id()));
+        assert_eq!(router.gamepads(), &[
+            Gamepad { id: first, name: "First".to_string() },
+            Gamepad { id: second, name: "Second".to_string() },
+        ]);
+        backend.disconnect(first);
+        router.poll(Instant::now(), Some(window_id()));
+        assert_eq!(router.gamepads(), &[Gamepad { id: second, name: "Second".to_string() }]);
+    }
+
+    #[test]
+    fn events_without_focus_are_dropped_but_tracked() {
+        let backend = MockGamepadBackend::new();
+        let mut router = router(&backend);
+        let id = backend.connect("Pad");
+        backend.press(id, GamepadButton::Start);
+        assert!(router.poll(Instant::now(), None).is_empty());
+        assert_eq!(router.gamepads().len(), 1);
+        backend.move_axis(id, GamepadAxis::RightTrigger, 1.0);
+        let routed = router.poll(Instant::now(), Some(window_id()));
+        assert_eq!(routed.len(), 1);
+    }
+
+    #[test]
+    fn sending_wakes_the_loop() {
+        let wakes = Arc::new(AtomicUsize::new(0));
+        let backend = MockGamepadBackend::new();
+        let counter = wakes.clone();
+        let mut router = GamepadRouter::default();
+        router.set_backend(Box::new(backend.clone()), Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+        let id = backend.connect("Pad");
+        backend.press(id, GamepadButton::North);
+        assert_eq!(wakes.load(Ordering::SeqCst), 2);
+    }
+}
@@ -3,7 +3,7 @@ use std::num::NonZeroU32;
 use glutin::config::Config;
 use glutin::context::{ContextApi, ContextAttributesBuilder, NotCurrentGlContext, PossiblyCurrentContext};
 use glutin::display::{GetGlDisplay, GlDisplay};
-use glutin::surface::{GlSurface, SurfaceAttributesBuilder, WindowSurface};
+use glutin::surface::{GlSurface, SurfaceAttributesBuilder, SwapInterval, WindowSurface};
 use glutin::surface::Surface;
 use raw_window_handle::RawWindowHandle;
 
@@ -13,7 +13,7 @@ pub struct GlutinGraphic {
 }
 
 impl GlutinGraphic {
-    pub fn new((width, height): (u32, u32), raw_window_handle: RawWindowHandle, gl_config: Config,) -> Self{
+    pub fn new((width, height): (u32, u32), raw_window_handle: RawWindowHandle, gl_config: Config, vsync: bool) -> Self{
         
         let context_attributes = ContextAttributesBuilder::new().build(Some(raw_window_handle));
         let fallback_context_attributes = ContextAttributesBuilder::new()
@@ -51,6 +51,14 @@ impl GlutinGraphic {
             .make_current(&gl_surface)
             .expect("Could not make GL context current when setting up skia renderer");
 
+        // Sync buffer swaps to the display refresh so animation frames are paced by vsync.
+        let swap_interval = if vsync {
+            SwapInterval::Wait(NonZeroU32::new(1).unwrap())
+        } else {
+            SwapInterval::DontWait
+        };
+        let _ = gl_surface.set_swap_interval(&gl_context, swap_interval);
+
         gl::load_with(|s| {
             gl_config
                 .display()
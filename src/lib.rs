@@ -1,13 +1,29 @@
 mod graphic;
 pub mod window;
+pub mod window_control;
+pub mod window_state;
+mod work_area;
+pub mod config_file;
+pub mod window_config;
 mod skia;
 mod glutin_graphic;
 pub mod application;
 mod custom_event;
+mod lifecycle;
+mod window_tree;
 pub mod event_handler;
 pub mod ui;
 pub mod context;
 pub mod event;
+pub mod animation;
+pub mod clock;
+pub mod scheduler;
+pub mod monitor;
+pub mod single_instance;
+pub mod shortcut;
+pub mod clipboard;
+#[cfg(feature = "gamepad")]
+pub mod gamepad;
 
 pub use skia_safe;
 
@@ -0,0 +1,72 @@
+/// Decides on exit requests and the last window closing, apart from the event loop so it can be tested.
+#[derive(Default)]
+pub(crate) struct Lifecycle {
+    /// Set once an exit is decided, while the windows still open are closed.
+    exiting: bool,
+}
+
+impl Lifecycle {
+    pub fn is_exiting(&self) -> bool {
+        self.exiting
+    }
+
+    /// Returns whether to exit, `allowed` being the answer of the exit requested handler and `open_windows`
+    /// the windows open once it returned. Without any window the exit can't be cancelled, nothing would be
+    /// left for the user to quit the app from.
+    pub fn request_exit(&mut self, allowed: bool, open_windows: usize) -> bool {
+        if self.exiting {
+            return false;
+        }
+        self.exiting = allowed || open_windows == 0;
+        self.exiting
+    }
+
+    /// Whether the last window closed handler runs after a window was closed, which it doesn't for the
+    /// windows an exit closes.
+    pub fn runs_last_window_closed(&self, open_windows: usize) -> bool {
+        open_windows == 0 && !self.exiting
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_goes_ahead_unless_cancelled() {
+        let mut lifecycle = Lifecycle::default();
+        assert!(lifecycle.request_exit(true, 2));
+        assert!(lifecycle.is_exiting());
+    }
+
+    #[test]
+    fn cancelled_exit_keeps_running_with_windows_open() {
+        let mut lifecycle = Lifecycle::default();
+        assert!(!lifecycle.request_exit(false, 1));
+        assert!(!lifecycle.is_exiting());
+        assert!(lifecycle.request_exit(true, 1));
+    }
+
+    #[test]
+    fn cancelled_exit_without_windows_exits_anyway() {
+        let mut lifecycle = Lifecycle::default();
+        assert!(lifecycle.request_exit(false, 0));
+        assert!(lifecycle.is_exiting());
+    }
+
+    #[test]
+    fn exit_is_decided_once() {
+        let mut lifecycle = Lifecycle::default();
+        assert!(lifecycle.request_exit(true, 1));
+        assert!(!lifecycle.request_exit(true, 1));
+    }
+
+    #[test]
+    fn last_window_closed_runs_only_outside_of_an_exit() {
+        let mut lifecycle = Lifecycle::default();
+        assert!(!lifecycle.runs_last_window_closed(1));
+        assert!(lifecycle.runs_last_window_closed(0));
+        lifecycle.request_exit(true, 1);
+        assert!(!lifecycle.runs_last_window_closed(0));
+    }
+}
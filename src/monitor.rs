@@ -0,0 +1,150 @@
+use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
+use winit::monitor::{MonitorHandle, VideoMode};
+use winit::window::WindowBuilder;
+use crate::work_area::{work_area, Area};
+
+/// Snapshot of a connected monitor, from `Context::available_monitors`.
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    handle: MonitorHandle,
+    is_primary: bool,
+}
+
+impl Monitor {
+    pub(crate) fn new(handle: MonitorHandle, is_primary: bool) -> Self {
+        Self {
+            handle,
+            is_primary,
+        }
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.handle.name()
+    }
+
+    pub fn is_primary(&self) -> bool {
+        self.is_primary
+    }
+
+    /// Top-left corner on the virtual desktop.
+    pub fn position(&self) -> PhysicalPosition<i32> {
+        self.handle.position()
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.handle.size()
+    }
+
+    /// Position and size of the part not covered by taskbars, docks or panels. The whole monitor where
+    /// the platform can't tell, such as on Wayland.
+    pub fn work_area(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        let area = work_area(&self.handle);
+        (area.position, area.size)
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.handle.scale_factor()
+    }
+
+    pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+        self.handle.refresh_rate_millihertz()
+    }
+
+    /// Video modes usable for exclusive fullscreen.
+    pub fn video_modes(&self) -> Vec<VideoMode> {
+        self.handle.video_modes().collect()
+    }
+
+    pub fn handle(&self) -> &MonitorHandle {
+        &self.handle
+    }
+
+    /// Positions windows built from `wb` in this monitor's work area. The requested inner size is converted with this
+    /// monitor's scale factor, so logical sizes come out right even when it differs from the current monitor.
+    pub(crate) fn place(&self, placement: MonitorPlacement, wb: WindowBuilder) -> WindowBuilder {
+        let inner_size = wb.window_attributes().inner_size.unwrap_or(LogicalSize::new(800.0, 600.0).into());
+        let (position, size) = placement.resolve(work_area(&self.handle), self.scale_factor(), inner_size);
+        wb.with_inner_size(size).with_position(position)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MonitorPlacement {
+    /// Centered in the work area.
+    Centered,
+    /// Relative to the top-left corner of the work area, logical positions use the monitor's scale factor.
+    At(Position),
+}
+
+impl MonitorPlacement {
+    /// Position and inner size in physical pixels of a window placed in `work_area` of a monitor with `scale_factor`.
+    fn resolve(&self, work_area: Area, scale_factor: f64, inner_size: Size) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        let size: PhysicalSize<u32> = inner_size.to_physical(scale_factor);
+        let offset = match *self {
+            MonitorPlacement::Centered => PhysicalPosition::new(
+                (work_area.size.width.saturating_sub(size.width) / 2) as i32,
+                (work_area.size.height.saturating_sub(size.height) / 2) as i32,
+            ),
+            MonitorPlacement::At(position) => position.to_physical(scale_factor),
+        };
+        let position = PhysicalPosition::new(work_area.position.x + offset.x, work_area.position.y + offset.y);
+        (position, size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::dpi::LogicalPosition;
+    use super::*;
+
+    /// A 1x monitor on the left, with a taskbar at the bottom.
+    fn left() -> Area {
+        Area::new(0, 0, 1920, 1040)
+    }
+
+    /// A 2x monitor on the right, with a menu bar at the top.
+    fn right() -> Area {
+        Area::new(1920, 50, 3840, 2110)
+    }
+
+    #[test]
+    fn logical_sizes_use_the_target_monitors_scale_factor() {
+        let size = Size::from(LogicalSize::new(800.0, 600.0));
+        let (_, on_left) = MonitorPlacement::Centered.resolve(left(), 1.0, size);
+        let (_, on_right) = MonitorPlacement::Centered.resolve(right(), 2.0, size);
+        assert_eq!(on_left, PhysicalSize::new(800, 600));
+        assert_eq!(on_right, PhysicalSize::new(1600, 1200));
+    }
+
+    #[test]
+    fn physical_sizes_are_kept() {
+        let size = Size::from(PhysicalSize::new(800, 600));
+        let (_, on_right) = MonitorPlacement::Centered.resolve(right(), 2.0, size);
+        assert_eq!(on_right, PhysicalSize::new(800, 600));
+    }
+
+    #[test]
+    fn centered_in_the_work_area() {
+        let size = Size::from(LogicalSize::new(800.0, 600.0));
+        let (position, _) = MonitorPlacement::Centered.resolve(left(), 1.0, size);
+        assert_eq!(position, PhysicalPosition::new(560, 220));
+        let (position, _) = MonitorPlacement::Centered.resolve(right(), 2.0, size);
+        assert_eq!(position, PhysicalPosition::new(1920 + 1120, 50 + 455));
+    }
+
+    #[test]
+    fn positions_are_relative_to_the_work_area() {
+        let size = Size::from(LogicalSize::new(400.0, 300.0));
+        let at = MonitorPlacement::At(Position::from(LogicalPosition::new(100.0, 20.0)));
+        assert_eq!(at.resolve(left(), 1.0, size).0, PhysicalPosition::new(100, 20));
+        assert_eq!(at.resolve(right(), 2.0, size).0, PhysicalPosition::new(2120, 90));
+        let at = MonitorPlacement::At(Position::from(PhysicalPosition::new(100, 20)));
+        assert_eq!(at.resolve(right(), 2.0, size).0, PhysicalPosition::new(2020, 70));
+    }
+
+    #[test]
+    fn larger_than_the_work_area_starts_at_its_corner() {
+        let size = Size::from(LogicalSize::new(2000.0, 1200.0));
+        assert_eq!(MonitorPlacement::Centered.resolve(left(), 1.0, size).0, PhysicalPosition::new(0, 0));
+    }
+}
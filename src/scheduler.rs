@@ -0,0 +1,210 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use crate::application::TimerId;
+
+struct Entry<T> {
+    deadline: Instant,
+    period: Option<Duration>,
+    value: T,
+}
+
+/// An entry taken out by `Scheduler::take_due`.
+pub struct Due<T> {
+    pub id: TimerId,
+    pub deadline: Instant,
+    period: Option<Duration>,
+    pub value: T,
+}
+
+/// Timeouts and intervals ordered by deadline, backing `Application`'s timers.
+/// It only knows the time it is given, so it can be driven by a `VirtualClock` without an event loop.
+pub struct Scheduler<T> {
+    entries: HashMap<TimerId, Entry<T>>,
+    last_id: TimerId,
+    /// Intervals taken out by `take_due` and not handed back yet.
+    running: HashSet<TimerId>,
+}
+
+impl<T> Scheduler<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            last_id: TimerId::default(),
+            running: HashSet::new(),
+        }
+    }
+
+    /// Schedules `value` once, `delay` after `now`.
+    pub fn add_timeout(&mut self, now: Instant, delay: Duration, value: T) -> TimerId {
+        self.insert(now + delay, None, value)
+    }
+
+    /// Schedules `value` every `period`, the first time one period after `now`.
+    pub fn add_interval(&mut self, now: Instant, period: Duration, value: T) -> TimerId {
+        self.insert(now + period, Some(period), value)
+    }
+
+    fn insert(&mut self, deadline: Instant, period: Option<Duration>, value: T) -> TimerId {
+        self.last_id = self.last_id.next();
+        self.entries.insert(self.last_id, Entry { deadline, period, value });
+        self.last_id
+    }
+
+    /// Works on intervals taken out by `take_due` as well, they won't be scheduled again.
+    pub fn remove(&mut self, id: TimerId) -> Option<T> {
+        self.running.remove(&id);
+        self.entries.remove(&id).map(|entry| entry.value)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.entries.values().map(|entry| entry.deadline).min()
+    }
+
+    /// Takes out the entries due at `now`, earliest first. Intervals keep running only once
+    /// handed back with `reschedule`.
+    pub fn take_due(&mut self, now: Instant) -> Vec<Due<T>> {
+        let mut due_ids: Vec<(Instant, TimerId)> = self.entries.iter()
+            .filter(|(_, entry)| entry.deadline <= now)
+            .map(|(id, entry)| (entry.deadline, *id))
+            .collect();
+        due_ids.sort();
+        let mut due = vec![];
+        for (_, id) in due_ids {
+            let Some(entry) = self.entries.remove(&id) else {
+                continue;
+            };
+            if entry.period.is_some() {
+                self.running.insert(id);
+            }
+            due.push(Due {
+                id,
+                deadline: entry.deadline,
+                period: entry.period,
+                value: entry.value,
+            });
+        }
+        due
+    }
+
+    /// Schedules an interval again after it ran, unless it was removed meanwhile.
+    /// The next deadline follows the previous one rather than `now` so the interval doesn't drift,
+    /// unless it fell a whole period behind.
+    pub fn reschedule(&mut self, due: Due<T>, now: Instant) {
+        let Some(period) = due.period else {
+            return;
+        };
+        if !self.running.remove(&due.id) {
+            return;
+        }
+        let last = if now >= due.deadline + period { now } else { due.deadline };
+        self.entries.insert(due.id, Entry {
+            deadline: last + period,
+            period: due.period,
+            value: due.value,
+        });
+    }
+}
+
+impl<T> Default for Scheduler<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, VirtualClock};
+
+    fn run_due(scheduler: &mut Scheduler<&'static str>, now: Instant) -> Vec<&'static str> {
+        let due = scheduler.take_due(now);
+        let values = due.iter().map(|due| due.value).collect();
+        for due in due {
+            scheduler.reschedule(due, now);
+        }
+        values
+    }
+
+    #[test]
+    fn timeouts_fire_once_in_deadline_order() {
+        let clock = VirtualClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.add_timeout(clock.now(), Duration::from_millis(30), "late");
+        scheduler.add_timeout(clock.now(), Duration::from_millis(10), "early");
+        clock.advance(Duration::from_millis(5));
+        assert!(run_due(&mut scheduler, clock.now()).is_empty());
+        clock.advance(Duration::from_millis(25));
+        assert_eq!(run_due(&mut scheduler, clock.now()), vec!["early", "late"]);
+        clock.advance(Duration::from_secs(1));
+        assert!(run_due(&mut scheduler, clock.now()).is_empty());
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn removed_timeout_never_fires() {
+        let clock = VirtualClock::new();
+        let mut scheduler = Scheduler::new();
+        let id = scheduler.add_timeout(clock.now(), Duration::from_millis(10), "removed");
+        assert_eq!(scheduler.remove(id), Some("removed"));
+        clock.advance(Duration::from_millis(10));
+        assert!(run_due(&mut scheduler, clock.now()).is_empty());
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        let clock = VirtualClock::new();
+        let mut scheduler = Scheduler::new();
+        let first = scheduler.add_timeout(clock.now(), Duration::ZERO, "first");
+        let second = scheduler.add_interval(clock.now(), Duration::from_millis(1), "second");
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn interval_keeps_its_phase() {
+        let clock = VirtualClock::new();
+        let start = clock.now();
+        let mut scheduler = Scheduler::new();
+        scheduler.add_interval(clock.now(), Duration::from_millis(10), "tick");
+        clock.advance(Duration::from_millis(13));
+        assert_eq!(run_due(&mut scheduler, clock.now()), vec!["tick"]);
+        assert_eq!(scheduler.next_deadline(), Some(start + Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn interval_far_behind_restarts_from_now() {
+        let clock = VirtualClock::new();
+        let mut scheduler = Scheduler::new();
+        scheduler.add_interval(clock.now(), Duration::from_millis(10), "tick");
+        clock.advance(Duration::from_millis(55));
+        assert_eq!(run_due(&mut scheduler, clock.now()), vec!["tick"]);
+        assert_eq!(scheduler.next_deadline(), Some(clock.now() + Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn interval_removed_while_running_stops() {
+        let clock = VirtualClock::new();
+        let mut scheduler = Scheduler::new();
+        let id = scheduler.add_interval(clock.now(), Duration::from_millis(10), "tick");
+        clock.advance(Duration::from_millis(10));
+        let due = scheduler.take_due(clock.now());
+        assert_eq!(scheduler.remove(id), None);
+        for due in due {
+            scheduler.reschedule(due, clock.now());
+        }
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn next_deadline_is_the_earliest() {
+        let clock = VirtualClock::new();
+        let mut scheduler = Scheduler::new();
+        assert_eq!(scheduler.next_deadline(), None);
+        scheduler.add_interval(clock.now(), Duration::from_millis(50), "interval");
+        scheduler.add_timeout(clock.now(), Duration::from_millis(20), "timeout");
+        assert_eq!(scheduler.next_deadline(), Some(clock.now() + Duration::from_millis(20)));
+    }
+}
@@ -0,0 +1,609 @@
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use winit::keyboard::{Key, KeyCode, NamedKey, PhysicalKey};
+use crate::config_file::{self, ConfigFileError};
+use crate::event::input::Modifiers;
+
+/// Names accepted for non-character keys. The first name of a key is the one it is displayed with.
+const NAMED_KEYS: &[(&str, NamedKey)] = &[
+    ("Enter", NamedKey::Enter),
+    ("Tab", NamedKey::Tab),
+    ("Space", NamedKey::Space),
+    ("Backspace", NamedKey::Backspace),
+    ("Delete", NamedKey::Delete),
+    ("Escape", NamedKey::Escape),
+    ("Insert", NamedKey::Insert),
+    ("Home", NamedKey::Home),
+    ("End", NamedKey::End),
+    ("PageUp", NamedKey::PageUp),
+    ("PageDown", NamedKey::PageDown),
+    ("Up", NamedKey::ArrowUp),
+    ("Down", NamedKey::ArrowDown),
+    ("Left", NamedKey::ArrowLeft),
+    ("Right", NamedKey::ArrowRight),
+    ("F1", NamedKey::F1),
+    ("F2", NamedKey::F2),
+    ("F3", NamedKey::F3),
+    ("F4", NamedKey::F4),
+    ("F5", NamedKey::F5),
+    ("F6", NamedKey::F6),
+    ("F7", NamedKey::F7),
+    ("F8", NamedKey::F8),
+    ("F9", NamedKey::F9),
+    ("F10", NamedKey::F10),
+    ("F11", NamedKey::F11),
+    ("F12", NamedKey::F12),
+    ("Return", NamedKey::Enter),
+    ("Esc", NamedKey::Escape),
+    ("Del", NamedKey::Delete),
+    ("ArrowUp", NamedKey::ArrowUp),
+    ("ArrowDown", NamedKey::ArrowDown),
+    ("ArrowLeft", NamedKey::ArrowLeft),
+    ("ArrowRight", NamedKey::ArrowRight),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ShortcutKey {
+    /// Lowercase, so `Ctrl+Shift+P` matches whether the layout reports `p` or `P`.
+    Character(String),
+    Named(NamedKey),
+}
+
+/// A key pressed with an exact set of modifiers, such as `Ctrl+Shift+P`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    pub modifiers: Modifiers,
+    pub key: ShortcutKey,
+}
+
+impl KeyCombo {
+    /// When the key doesn't type a Latin letter or digit, such as with a Cyrillic layout or
+    /// `Shift+1`, characters match by the physical key that types them on a US layout instead.
+    pub(crate) fn matches(&self, key: &Key, physical_key: PhysicalKey, modifiers: Modifiers) -> bool {
+        if self.modifiers != modifiers {
+            return false;
+        }
+        match (&self.key, key) {
+            (ShortcutKey::Named(named), Key::Named(key)) => named == key,
+            (ShortcutKey::Character(character), Key::Character(key)) if character == &key.to_lowercase() => true,
+            (ShortcutKey::Character(_), Key::Character(key)) if key.chars().all(|c| c.is_ascii_alphanumeric()) => false,
+            (ShortcutKey::Character(character), _) => {
+                let mut chars = character.chars();
+                match (chars.next().and_then(key_code), chars.next()) {
+                    (Some(code), None) => physical_key == PhysicalKey::Code(code),
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = ParseShortcutError;
+
+    /// Parses `+` separated modifiers followed by a key. `CmdOrCtrl` is `Cmd` on macOS and `Ctrl` elsewhere.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseShortcutError(s.to_string());
+        let (modifier_names, key_name) = match s.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None => match s.rsplit_once('+') {
+                Some((rest, key)) => (rest, key),
+                None => ("", s),
+            },
+        };
+        let mut modifiers = Modifiers::default();
+        for name in modifier_names.split('+').filter(|name| !name.is_empty()) {
+            match name.trim().to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers.control = true,
+                "shift" => modifiers.shift = true,
+                "alt" | "option" => modifiers.alt = true,
+                "cmd" | "command" | "meta" | "super" | "win" => modifiers.meta = true,
+                "cmdorctrl" | "mod" => match cfg!(target_os = "macos") {
+                    true => modifiers.meta = true,
+                    false => modifiers.control = true,
+                },
+                _ => return Err(error()),
+            }
+        }
+        let key_name = key_name.trim();
+        let key = match NAMED_KEYS.iter().find(|(name, _)| name.eq_ignore_ascii_case(key_name)) {
+            Some((_, named)) => ShortcutKey::Named(*named),
+            None if key_name.eq_ignore_ascii_case("plus") => ShortcutKey::Character("+".to_string()),
+            None if key_name.chars().count() == 1 => ShortcutKey::Character(key_name.to_lowercase()),
+            None => return Err(error()),
+        };
+        Ok(Self { modifiers, key })
+    }
+}
+
+impl Display for KeyCombo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.control {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.alt {
+            write!(f, "{}", if cfg!(target_os = "macos") { "Option+" } else { "Alt+" })?;
+        }
+        if self.modifiers.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.modifiers.meta {
+            write!(f, "{}", if cfg!(target_os = "macos") { "Cmd+" } else { "Super+" })?;
+        }
+        match &self.key {
+            ShortcutKey::Character(character) => write!(f, "{}", character.to_uppercase()),
+            ShortcutKey::Named(named) => {
+                let name = NAMED_KEYS.iter().find(|(_, key)| key == named).map(|(name, _)| *name).unwrap_or("?");
+                write!(f, "{}", name)
+            }
+        }
+    }
+}
+
+/// One key combo, or a chord of several pressed one after the other such as `Ctrl+K Ctrl+C`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Shortcut(pub Vec<KeyCombo>);
+
+impl Shortcut {
+    pub fn combos(&self) -> &[KeyCombo] {
+        &self.0
+    }
+
+    fn starts_with(&self, other: &Shortcut) -> bool {
+        self.0.starts_with(&other.0)
+    }
+}
+
+impl FromStr for Shortcut {
+    type Err = ParseShortcutError;
+
+    /// Parses combos separated by spaces.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let combos = s.split_whitespace().map(KeyCombo::from_str).collect::<Result<Vec<_>, _>>()?;
+        if combos.is_empty() {
+            return Err(ParseShortcutError(s.to_string()));
+        }
+        Ok(Self(combos))
+    }
+}
+
+impl Display for Shortcut {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, combo) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", combo)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseShortcutError(pub String);
+
+impl Display for ParseShortcutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid shortcut \"{}\"", self.0)
+    }
+}
+
+impl Error for ParseShortcutError {}
+
+/// Two commands bound to the same shortcut, or one bound to the start of the other's chord.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortcutConflict {
+    pub shortcut: Shortcut,
+    pub command: String,
+    pub other_shortcut: Shortcut,
+    pub other_command: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ShortcutMatch {
+    Command {
+        command: String,
+        /// Whether holding the keys down runs the command again on every key repeat.
+        repeatable: bool,
+    },
+    /// The keys so far start a chord, wait for the next one.
+    Pending,
+    None,
+}
+
+/// Commands and the shortcuts that trigger them. A triggered command reaches the UI of the window
+/// that had the keyboard focus as `UIEvent::Command`, instead of the key events.
+/// `Context::commands` applies to every window, `WindowContext::commands` to one window and wins over the former.
+/// Holding the keys of a shortcut down triggers its command once, unless it is made repeatable.
+/// When several commands share a shortcut, the one bound first is triggered.
+#[derive(Default)]
+pub struct CommandRegistry {
+    /// In the order the commands were first bound.
+    bindings: Vec<(String, Vec<Shortcut>)>,
+    repeatable: HashSet<String>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `shortcut` to the ones of `command`.
+    pub fn bind(&mut self, command: impl Into<String>, shortcut: Shortcut) {
+        let shortcuts = self.shortcuts_mut(command.into());
+        if !shortcuts.contains(&shortcut) {
+            shortcuts.push(shortcut);
+        }
+    }
+
+    /// Replaces the shortcuts of `command`, an empty list leaves it without any. A command that was
+    /// already bound keeps its place in the order.
+    pub fn set_bindings(&mut self, command: impl Into<String>, shortcuts: Vec<Shortcut>) {
+        *self.shortcuts_mut(command.into()) = shortcuts;
+    }
+
+    pub fn unbind(&mut self, command: &str) {
+        self.bindings.retain(|(bound, _)| bound != command);
+    }
+
+    fn shortcuts_mut(&mut self, command: String) -> &mut Vec<Shortcut> {
+        let index = match self.bindings.iter().position(|(bound, _)| *bound == command) {
+            Some(index) => index,
+            None => {
+                self.bindings.push((command, vec![]));
+                self.bindings.len() - 1
+            }
+        };
+        &mut self.bindings[index].1
+    }
+
+    /// Makes `command` run again on every key repeat while its shortcut is held down, e.g. for undo.
+    pub fn set_repeatable(&mut self, command: impl Into<String>, repeatable: bool) {
+        let command = command.into();
+        match repeatable {
+            true => self.repeatable.insert(command),
+            false => self.repeatable.remove(&command),
+        };
+    }
+
+    pub fn is_repeatable(&self, command: &str) -> bool {
+        self.repeatable.contains(command)
+    }
+
+    pub fn bindings(&self, command: &str) -> &[Shortcut] {
+        self.bindings.iter()
+            .find(|(bound, _)| bound == command)
+            .map(|(_, shortcuts)| shortcuts.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Bound commands, in the order they were first bound.
+    pub fn commands(&self) -> impl Iterator<Item = &str> {
+        self.bindings.iter().map(|(command, _)| command.as_str())
+    }
+
+    /// The command bound to `shortcut`, the first one bound if several are.
+    pub fn command_for(&self, shortcut: &Shortcut) -> Option<&str> {
+        self.bindings.iter()
+            .find(|(_, shortcuts)| shortcuts.contains(shortcut))
+            .map(|(command, _)| command.as_str())
+    }
+
+    /// Pairs of bindings where only one of the commands could ever be triggered.
+    pub fn conflicts(&self) -> Vec<ShortcutConflict> {
+        let bindings = self.sorted_bindings();
+        let mut conflicts = vec![];
+        for (index, (command, shortcut)) in bindings.iter().enumerate() {
+            for (other_command, other_shortcut) in &bindings[index + 1..] {
+                conflicts.extend(conflict(command, shortcut, other_command, other_shortcut));
+            }
+        }
+        conflicts
+    }
+
+    /// Pairs of a binding of this registry and one of `other` where only one of the commands could ever be
+    /// triggered, such as between window and application commands. Bindings of the same command don't conflict.
+    pub fn conflicts_with(&self, other: &CommandRegistry) -> Vec<ShortcutConflict> {
+        let other_bindings = other.sorted_bindings();
+        let mut conflicts = vec![];
+        for (command, shortcut) in self.sorted_bindings() {
+            for (other_command, other_shortcut) in &other_bindings {
+                conflicts.extend(conflict(command, shortcut, other_command, other_shortcut));
+            }
+        }
+        conflicts
+    }
+
+    fn sorted_bindings(&self) -> Vec<(&String, &Shortcut)> {
+        let mut bindings: Vec<(&String, &Shortcut)> = self.bindings.iter()
+            .flat_map(|(command, shortcuts)| shortcuts.iter().map(move |shortcut| (command, shortcut)))
+            .collect();
+        bindings.sort_by(|a, b| a.0.cmp(b.0));
+        bindings
+    }
+
+    /// Replaces the shortcuts of every command listed in `file`. Nothing changes if one of them is invalid.
+    /// Commands that weren't bound yet are added in the order of their names.
+    pub fn apply(&mut self, file: &KeyBindingsFile) -> Result<(), ParseShortcutError> {
+        let mut parsed = vec![];
+        for (command, shortcuts) in &file.bindings {
+            let shortcuts = shortcuts.iter().map(|shortcut| shortcut.parse()).collect::<Result<Vec<_>, _>>()?;
+            parsed.push((command.clone(), shortcuts));
+        }
+        for (command, shortcuts) in parsed {
+            self.set_bindings(command, shortcuts);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn lookup(&self, sequence: &[(Key, PhysicalKey, Modifiers)]) -> ShortcutMatch {
+        let mut pending = false;
+        for (command, shortcuts) in &self.bindings {
+            for shortcut in shortcuts {
+                if shortcut.0.len() < sequence.len() {
+                    continue;
+                }
+                let matched = shortcut.0.iter()
+                    .zip(sequence)
+                    .all(|(combo, (key, physical_key, modifiers))| combo.matches(key, *physical_key, *modifiers));
+                if !matched {
+                    continue;
+                }
+                if shortcut.0.len() == sequence.len() {
+                    return ShortcutMatch::Command {
+                        command: command.clone(),
+                        repeatable: self.is_repeatable(command),
+                    };
+                }
+                pending = true;
+            }
+        }
+        match pending {
+            true => ShortcutMatch::Pending,
+            false => ShortcutMatch::None,
+        }
+    }
+}
+
+fn conflict(command: &str, shortcut: &Shortcut, other_command: &str, other_shortcut: &Shortcut) -> Option<ShortcutConflict> {
+    if command == other_command {
+        return None;
+    }
+    if !shortcut.starts_with(other_shortcut) && !other_shortcut.starts_with(shortcut) {
+        return None;
+    }
+    Some(ShortcutConflict {
+        shortcut: shortcut.clone(),
+        command: command.to_string(),
+        other_shortcut: other_shortcut.clone(),
+        other_command: other_command.to_string(),
+    })
+}
+
+/// User overrides of command shortcuts, e.g. in TOML:
+///
+/// ```toml
+/// [bindings]
+/// "file.save" = ["Ctrl+S"]
+/// "editor.comment" = ["Ctrl+K Ctrl+C", "Ctrl+/"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KeyBindingsFile {
+    #[serde(default)]
+    pub bindings: BTreeMap<String, Vec<String>>,
+}
+
+impl KeyBindingsFile {
+    /// Reads a `.toml` or `.json` file, chosen by its extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigFileError> {
+        config_file::load(path)
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, ConfigFileError> {
+        config_file::from_toml(content)
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, ConfigFileError> {
+        config_file::from_json(content)
+    }
+}
+
+/// The key that types `character` on a US layout.
+fn key_code(character: char) -> Option<KeyCode> {
+    let code = match character {
+        'a' => KeyCode::KeyA,
+        'b' => KeyCode::KeyB,
+        'c' => KeyCode::KeyC,
+        'd' => KeyCode::KeyD,
+        'e' => KeyCode::KeyE,
+        'f' => KeyCode::KeyF,
+        'g' => KeyCode::KeyG,
+        'h' => KeyCode::KeyH,
+        'i' => KeyCode::KeyI,
+        'j' => KeyCode::KeyJ,
+        'k' => KeyCode::KeyK,
+        'l' => KeyCode::KeyL,
+        'm' => KeyCode::KeyM,
+        'n' => KeyCode::KeyN,
+        'o' => KeyCode::KeyO,
+        'p' => KeyCode::KeyP,
+        'q' => KeyCode::KeyQ,
+        'r' => KeyCode::KeyR,
+        's' => KeyCode::KeyS,
+        't' => KeyCode::KeyT,
+        'u' => KeyCode::KeyU,
+        'v' => KeyCode::KeyV,
+        'w' => KeyCode::KeyW,
+        'x' => KeyCode::KeyX,
+        'y' => KeyCode::KeyY,
+        'z' => KeyCode::KeyZ,
+        '0' => KeyCode::Digit0,
+        '1' => KeyCode::Digit1,
+        '2' => KeyCode::Digit2,
+        '3' => KeyCode::Digit3,
+        '4' => KeyCode::Digit4,
+        '5' => KeyCode::Digit5,
+        '6' => KeyCode::Digit6,
+        '7' => KeyCode::Digit7,
+        '8' => KeyCode::Digit8,
+        '9' => KeyCode::Digit9,
+        _ => return None,
+    };
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::keyboard::{NativeKeyCode, SmolStr};
+    use super::*;
+
+    fn shortcut(s: &str) -> Shortcut {
+        s.parse().unwrap()
+    }
+
+    fn ctrl() -> Modifiers {
+        Modifiers { control: true, ..Modifiers::default() }
+    }
+
+    fn character(c: &str, code: KeyCode, modifiers: Modifiers) -> (Key, PhysicalKey, Modifiers) {
+        (Key::Character(SmolStr::new(c)), PhysicalKey::Code(code), modifiers)
+    }
+
+    fn command(command: &str) -> ShortcutMatch {
+        ShortcutMatch::Command {
+            command: command.to_string(),
+            repeatable: false,
+        }
+    }
+
+    #[test]
+    fn parses_modifiers_and_keys() {
+        let combo: KeyCombo = "Ctrl+Shift+P".parse().unwrap();
+        assert_eq!(combo.modifiers, Modifiers { control: true, shift: true, ..Modifiers::default() });
+        assert_eq!(combo.key, ShortcutKey::Character("p".to_string()));
+        let combo: KeyCombo = "alt+esc".parse().unwrap();
+        assert_eq!(combo.modifiers, Modifiers { alt: true, ..Modifiers::default() });
+        assert_eq!(combo.key, ShortcutKey::Named(NamedKey::Escape));
+        assert_eq!("Ctrl++".parse::<KeyCombo>().unwrap().key, ShortcutKey::Character("+".to_string()));
+        assert_eq!("Ctrl+Plus".parse::<KeyCombo>().unwrap().key, ShortcutKey::Character("+".to_string()));
+    }
+
+    #[test]
+    fn parses_chords() {
+        let chord = shortcut("Ctrl+K  Ctrl+C");
+        assert_eq!(chord.combos().len(), 2);
+        assert_eq!(chord.to_string(), "Ctrl+K Ctrl+C");
+    }
+
+    #[test]
+    fn rejects_invalid_shortcuts() {
+        for invalid in ["", "Ctrl+", "Hyper+A", "Ctrl+Foo"] {
+            assert_eq!(invalid.parse::<Shortcut>(), Err(ParseShortcutError(invalid.to_string())), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn looks_up_commands_and_chords() {
+        let mut commands = CommandRegistry::new();
+        commands.bind("file.save", shortcut("Ctrl+S"));
+        commands.bind("editor.comment", shortcut("Ctrl+K Ctrl+C"));
+        let ctrl_s = character("s", KeyCode::KeyS, ctrl());
+        let ctrl_k = character("k", KeyCode::KeyK, ctrl());
+        let ctrl_c = character("c", KeyCode::KeyC, ctrl());
+        assert_eq!(commands.lookup(std::slice::from_ref(&ctrl_s)), command("file.save"));
+        assert_eq!(commands.lookup(std::slice::from_ref(&ctrl_k)), ShortcutMatch::Pending);
+        assert_eq!(commands.lookup(&[ctrl_k.clone(), ctrl_c]), command("editor.comment"));
+        assert_eq!(commands.lookup(&[ctrl_k, ctrl_s]), ShortcutMatch::None);
+        assert_eq!(commands.lookup(&[character("c", KeyCode::KeyC, Modifiers::default())]), ShortcutMatch::None);
+    }
+
+    #[test]
+    fn shared_shortcut_triggers_the_command_bound_first() {
+        for _ in 0..8 {
+            let mut commands = CommandRegistry::new();
+            for name in ["file.save", "file.save_all", "file.save_as", "file.store"] {
+                commands.bind(name, shortcut("Ctrl+S"));
+            }
+            assert_eq!(commands.lookup(&[character("s", KeyCode::KeyS, ctrl())]), command("file.save"));
+            assert_eq!(commands.command_for(&shortcut("Ctrl+S")), Some("file.save"));
+            commands.set_bindings("file.save", vec![]);
+            assert_eq!(commands.lookup(&[character("s", KeyCode::KeyS, ctrl())]), command("file.save_all"));
+            // Rebinding keeps the command's place.
+            commands.set_bindings("file.save", vec![shortcut("Ctrl+S")]);
+            assert_eq!(commands.commands().collect::<Vec<_>>(), ["file.save", "file.save_all", "file.save_as", "file.store"]);
+            commands.unbind("file.save");
+            assert_eq!(commands.command_for(&shortcut("Ctrl+S")), Some("file.save_all"));
+        }
+    }
+
+    #[test]
+    fn non_latin_layouts_match_by_physical_key() {
+        let mut commands = CommandRegistry::new();
+        commands.bind("file.save", shortcut("Ctrl+S"));
+        assert_eq!(commands.lookup(&[character("ы", KeyCode::KeyS, ctrl())]), command("file.save"));
+        let unidentified = (Key::Character(SmolStr::new("ы")), PhysicalKey::Unidentified(NativeKeyCode::Unidentified), ctrl());
+        assert_eq!(commands.lookup(&[unidentified]), ShortcutMatch::None);
+    }
+
+    #[test]
+    fn repeatable_commands_say_so() {
+        let mut commands = CommandRegistry::new();
+        commands.bind("edit.undo", shortcut("Ctrl+Z"));
+        commands.set_repeatable("edit.undo", true);
+        let expected = ShortcutMatch::Command {
+            command: "edit.undo".to_string(),
+            repeatable: true,
+        };
+        assert_eq!(commands.lookup(&[character("z", KeyCode::KeyZ, ctrl())]), expected);
+        commands.set_repeatable("edit.undo", false);
+        assert_eq!(commands.lookup(&[character("z", KeyCode::KeyZ, ctrl())]), command("edit.undo"));
+    }
+
+    #[test]
+    fn applies_key_bindings_files() {
+        let mut commands = CommandRegistry::new();
+        commands.bind("file.save", shortcut("Ctrl+S"));
+        commands.bind("file.open", shortcut("Ctrl+O"));
+        let file = KeyBindingsFile::from_toml(r#"
+            [bindings]
+            "file.save" = ["Ctrl+Shift+S", "F2"]
+            "editor.comment" = ["Ctrl+K Ctrl+C"]
+        "#).unwrap();
+        commands.apply(&file).unwrap();
+        assert_eq!(commands.bindings("file.save"), &[shortcut("Ctrl+Shift+S"), shortcut("F2")]);
+        assert_eq!(commands.bindings("file.open"), &[shortcut("Ctrl+O")]);
+        assert_eq!(commands.bindings("editor.comment"), &[shortcut("Ctrl+K Ctrl+C")]);
+    }
+
+    #[test]
+    fn invalid_key_bindings_file_changes_nothing() {
+        let mut commands = CommandRegistry::new();
+        commands.bind("file.save", shortcut("Ctrl+S"));
+        let file = KeyBindingsFile::from_json(r#"{"bindings": {"file.save": ["Ctrl+Shift+S"], "file.open": ["Ctrl+Nope"]}}"#).unwrap();
+        assert_eq!(commands.apply(&file), Err(ParseShortcutError("Ctrl+Nope".to_string())));
+        assert_eq!(commands.bindings("file.save"), &[shortcut("Ctrl+S")]);
+    }
+
+    #[test]
+    fn finds_conflicts_within_and_across_registries() {
+        let mut application = CommandRegistry::new();
+        application.bind("file.save", shortcut("Ctrl+S"));
+        application.bind("file.save_as", shortcut("Ctrl+S"));
+        application.bind("editor.comment", shortcut("Ctrl+K Ctrl+C"));
+        assert_eq!(application.conflicts().len(), 1);
+        let mut window = CommandRegistry::new();
+        window.bind("file.save", shortcut("Ctrl+S"));
+        window.bind("panel.close", shortcut("Ctrl+K"));
+        let conflicts = window.conflicts_with(&application);
+        let pairs: HashSet<(&str, &str)> = conflicts.iter()
+            .map(|conflict| (conflict.command.as_str(), conflict.other_command.as_str()))
+            .collect();
+        let expected = HashSet::from([("file.save", "file.save_as"), ("panel.close", "editor.comment")]);
+        assert_eq!(pairs, expected);
+    }
+}
@@ -0,0 +1,304 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+/// Command line of an application instance, forwarded to the running instance by `launch_single_instance`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceArgs {
+    args: Vec<String>,
+    files: Vec<PathBuf>,
+    cwd: Option<PathBuf>,
+}
+
+impl InstanceArgs {
+    /// Arguments of the current process, without the program name.
+    pub fn from_env() -> Self {
+        let cwd = std::env::current_dir().ok();
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let files = args.iter()
+            .filter(|arg| !arg.starts_with('-'))
+            .map(|arg| match &cwd {
+                Some(cwd) => cwd.join(arg),
+                None => PathBuf::from(arg),
+            })
+            .filter(|path| path.exists())
+            .map(|path| path.canonicalize().unwrap_or(path))
+            .collect();
+        Self {
+            args,
+            files,
+            cwd,
+        }
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Arguments naming existing files, as absolute paths.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Working directory of the instance that was launched.
+    pub fn cwd(&self) -> Option<&Path> {
+        self.cwd.as_deref()
+    }
+}
+
+/// Why `launch_single_instance` couldn't tell whether another instance is running.
+#[derive(Debug)]
+pub enum SingleInstanceError {
+    /// The lock file couldn't be opened or locked.
+    Lock(io::Error),
+    /// The socket later instances connect to couldn't be created.
+    Listen(io::Error),
+    /// Another instance holds the lock, but its socket didn't take the arguments.
+    Forward(io::Error),
+}
+
+impl Display for SingleInstanceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SingleInstanceError::Lock(error) => write!(f, "failed to lock the instance lock file: {}", error),
+            SingleInstanceError::Listen(error) => write!(f, "failed to listen for later instances: {}", error),
+            SingleInstanceError::Forward(error) => write!(f, "failed to forward arguments to the running instance: {}", error),
+        }
+    }
+}
+
+impl Error for SingleInstanceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SingleInstanceError::Lock(error) | SingleInstanceError::Listen(error) | SingleInstanceError::Forward(error) => Some(error),
+        }
+    }
+}
+
+pub(crate) enum Acquired {
+    /// No other instance runs, this one does from now on.
+    First(Instance),
+    /// The arguments went to the instance already running.
+    Forwarded,
+}
+
+/// The running instance, until dropped. Later instances can connect as soon as it is acquired,
+/// their arguments are read once `listen` is called.
+pub(crate) struct Instance {
+    #[cfg(unix)]
+    listener: std::os::unix::net::UnixListener,
+    #[cfg(unix)]
+    path: PathBuf,
+    #[cfg(unix)]
+    _lock: std::fs::File,
+}
+
+#[cfg(unix)]
+impl Drop for Instance {
+    fn drop(&mut self) {
+        // Removed before the lock is released, so the next instance never sees the socket going away.
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// How long a later instance retries while the first one holds the lock but has no socket yet, which
+/// only lasts from taking the lock to binding the socket.
+#[cfg(unix)]
+const CONNECT_ATTEMPTS: u32 = 5;
+#[cfg(unix)]
+const CONNECT_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(20);
+/// How long the first instance waits for the arguments of a later one that connected.
+#[cfg(unix)]
+const READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Becomes the running instance of `app_id`, listening for later instances on a Unix domain socket in the
+/// runtime directory, or forwards `args` to the instance already running.
+#[cfg(unix)]
+pub(crate) fn acquire(app_id: &str, args: &InstanceArgs) -> Result<Acquired, SingleInstanceError> {
+    acquire_in(&dirs::runtime_dir().unwrap_or_else(std::env::temp_dir), app_id, args)
+}
+
+/// The running instance holds an `flock` on a lock file next to the socket, so two instances starting
+/// at once can't both take over the socket.
+#[cfg(unix)]
+fn acquire_in(dir: &Path, app_id: &str, args: &InstanceArgs) -> Result<Acquired, SingleInstanceError> {
+    use std::io::{ErrorKind, Write};
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let name: String = app_id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    let path = dir.join(format!("{}.sock", name));
+    let lock_path = dir.join(format!("{}.lock", name));
+    let data = serde_json::to_vec(args).map_err(io::Error::from).map_err(SingleInstanceError::Forward)?;
+    let forward = |mut stream: UnixStream| stream.write_all(&data);
+
+    let lock = std::fs::OpenOptions::new().create(true).truncate(false).write(true).open(lock_path)
+        .map_err(SingleInstanceError::Lock)?;
+    if unsafe { libc::flock(lock.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+        let error = io::Error::last_os_error();
+        if error.kind() != ErrorKind::WouldBlock {
+            return Err(SingleInstanceError::Lock(error));
+        }
+        // Another instance runs, though it may not be listening yet.
+        let mut last_error = None;
+        for _ in 0..CONNECT_ATTEMPTS {
+            match UnixStream::connect(&path).and_then(forward) {
+                Ok(()) => return Ok(Acquired::Forwarded),
+                Err(error) => last_error = Some(error),
+            }
+            std::thread::sleep(CONNECT_RETRY_DELAY);
+        }
+        return Err(SingleInstanceError::Forward(last_error.unwrap_or_else(|| ErrorKind::TimedOut.into())));
+    }
+
+    match UnixStream::connect(&path) {
+        // Only an instance that doesn't take the lock, e.g. an older version, can be listening here.
+        Ok(stream) => {
+            if forward(stream).is_ok() {
+                return Ok(Acquired::Forwarded);
+            }
+        }
+        // Nobody listens, so the socket is left over from an instance that didn't shut down cleanly.
+        Err(error) if error.kind() == ErrorKind::ConnectionRefused => {
+            let _ = std::fs::remove_file(&path);
+        }
+        Err(_) => {}
+    }
+    let listener = UnixListener::bind(&path).map_err(SingleInstanceError::Listen)?;
+    Ok(Acquired::First(Instance { listener, path, _lock: lock }))
+}
+
+impl Instance {
+    /// Hands the arguments of every later instance to `deliver` on a background thread, until it returns `false`.
+    #[cfg(unix)]
+    pub fn listen(&self, deliver: impl Fn(InstanceArgs) -> bool + Send + 'static) -> Result<(), SingleInstanceError> {
+        use std::io::Read;
+        let listener = self.listener.try_clone().map_err(SingleInstanceError::Listen)?;
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                // A client that connects without ever closing its end mustn't block the others.
+                if stream.set_read_timeout(Some(READ_TIMEOUT)).is_err() {
+                    continue;
+                }
+                let mut data = vec![];
+                if stream.read_to_end(&mut data).is_err() {
+                    continue;
+                }
+                if let Ok(args) = serde_json::from_slice::<InstanceArgs>(&data) {
+                    if !deliver(args) {
+                        break;
+                    }
+                }
+            }
+        });
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn listen(&self, _deliver: impl Fn(InstanceArgs) -> bool + Send + 'static) -> Result<(), SingleInstanceError> {
+        Ok(())
+    }
+}
+
+/// Single instance detection isn't implemented on this platform: every instance acts as the first one and
+/// runs on its own, later instances are never forwarded.
+#[cfg(not(unix))]
+pub(crate) fn acquire(_app_id: &str, _args: &InstanceArgs) -> Result<Acquired, SingleInstanceError> {
+    Ok(Acquired::First(Instance {}))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use super::*;
+
+    /// A fresh directory, standing in for the runtime directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("skia-desktop-{}-{}-{}", name, std::process::id(), nanos));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn args(args: &[&str]) -> InstanceArgs {
+        InstanceArgs {
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            files: vec![],
+            cwd: None,
+        }
+    }
+
+    fn first(dir: &Path) -> Instance {
+        match acquire_in(dir, "app", &args(&[])).unwrap() {
+            Acquired::First(instance) => instance,
+            Acquired::Forwarded => panic!("forwarded with no instance running"),
+        }
+    }
+
+    #[test]
+    fn later_instances_forward_their_arguments() {
+        let dir = temp_dir("forward");
+        let instance = first(&dir);
+        let (sender, receiver) = mpsc::channel();
+        instance.listen(move |args| sender.send(args).is_ok()).unwrap();
+
+        for arg in ["a.txt", "b.txt"] {
+            assert!(matches!(acquire_in(&dir, "app", &args(&[arg])), Ok(Acquired::Forwarded)));
+            let received = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(received.args(), &[arg.to_string()]);
+        }
+        drop(instance);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn arguments_sent_before_listening_are_kept() {
+        let dir = temp_dir("backlog");
+        let instance = first(&dir);
+        assert!(matches!(acquire_in(&dir, "app", &args(&["early"])), Ok(Acquired::Forwarded)));
+        let (sender, receiver) = mpsc::channel();
+        instance.listen(move |args| sender.send(args).is_ok()).unwrap();
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)).unwrap().args(), &["early".to_string()]);
+        drop(instance);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn next_instance_takes_over_after_the_first_exits() {
+        let dir = temp_dir("takeover");
+        drop(first(&dir));
+        assert!(!dir.join("app.sock").exists());
+        drop(first(&dir));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn stale_socket_is_replaced() {
+        let dir = temp_dir("stale");
+        // Bound by an instance that crashed, nobody listens any more.
+        drop(std::os::unix::net::UnixListener::bind(dir.join("app.sock")).unwrap());
+        drop(first(&dir));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn lock_holder_without_a_socket_is_reported() {
+        let dir = temp_dir("no-socket");
+        let instance = first(&dir);
+        std::fs::remove_file(dir.join("app.sock")).unwrap();
+        assert!(matches!(acquire_in(&dir, "app", &args(&[])), Err(SingleInstanceError::Forward(_))));
+        drop(instance);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn missing_directory_is_reported() {
+        let dir = temp_dir("missing").join("missing");
+        assert!(matches!(acquire_in(&dir, "app", &args(&[])), Err(SingleInstanceError::Lock(_))));
+    }
+}
@@ -34,7 +34,6 @@ impl SkiaGLGraphic {
             FramebufferInfo {
                 fboid: fboid.try_into().unwrap(),
                 format: Format::RGBA8.into(),
-                ..Default::default()
             }
         };
         let num_samples = gl_config.num_samples() as usize;
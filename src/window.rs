@@ -1,34 +1,85 @@
 use glutin::config::{ConfigTemplateBuilder, Config as GLConfig, GlConfig};
 use glutin_winit::DisplayBuilder;
 use raw_window_handle::HasRawWindowHandle;
-use winit::dpi::PhysicalSize;
+use std::any::Any;
+use std::path::PathBuf;
+use std::time::Instant;
+use skia_safe::Size;
+use winit::dpi::{PhysicalPosition, PhysicalSize, Position};
 use winit::event::WindowEvent;
 use winit::event_loop::{EventLoopWindowTarget};
+use winit::keyboard::{Key, NamedKey, PhysicalKey};
 use winit::window::{WindowBuilder, WindowId};
 use winit::window::Window as WinitWindow;
 use crate::application::{Application, TimerId};
 use crate::context::context::Context;
 use crate::context::window_context::WindowContext;
 use crate::custom_event::CustomEvent;
+use crate::event::gesture::LONG_PRESS_TIME;
+use crate::event::input::{FileDragEvent, Modifiers};
+use crate::event::input_event::InputEvent;
+use crate::event::pipeline::InputPipeline;
 use crate::event::ui_event::UIEvent;
+use crate::event::window_message::WindowMessage;
+#[cfg(feature = "gamepad")]
+use crate::gamepad::GamepadEvent;
 use crate::graphic::Graphic;
+use crate::shortcut::ShortcutMatch;
 use crate::ui::UI;
+use crate::window_config::GraphicsSettings;
+use crate::window_control::WindowControl;
+
+/// Files winit reports one by one, delivered together.
+#[derive(Default)]
+struct FileDrag {
+    paths: Vec<PathBuf>,
+    dropped: Vec<PathBuf>,
+    entered: bool,
+    /// Files were reported since the last `FileDragOver`.
+    hovered: bool,
+    accepted: bool,
+}
 
 pub struct Window {
     inner_window: WinitWindow,
     graphic: Graphic,
     ui: Box<dyn UI>,
+    fullscreen: bool,
+    pipeline: InputPipeline,
+    /// Keys pressed so far of a shortcut chord.
+    pending_chord: Vec<(Key, PhysicalKey, Modifiers)>,
+    /// Keys held down whose press was used by a shortcut, so their repeats and release are too.
+    /// Each has the command to run again on repeats, if it is repeatable.
+    shortcut_keys: Vec<(Key, PhysicalKey, Option<String>)>,
+    file_drag: Option<FileDrag>,
+    created_at: Instant,
 }
 
 impl Window {
     pub fn new<T: 'static>(wb: WindowBuilder, event_loop: &EventLoopWindowTarget<T>, ui: impl UI + 'static) -> Self {
-        let (window, gl_config) = create_window_and_gl_config(wb, event_loop);
+        Self::with_graphics(wb, GraphicsSettings::default(), event_loop, Box::new(ui))
+    }
+
+    pub fn with_graphics<T: 'static>(wb: WindowBuilder, graphics: GraphicsSettings, event_loop: &EventLoopWindowTarget<T>, ui: Box<dyn UI>) -> Self {
+        Self::create(wb, graphics, event_loop, ui, Instant::now())
+    }
+
+    /// `created_at` is read from the application's clock, recorded input is timed from it.
+    pub(crate) fn create<T: 'static>(wb: WindowBuilder, graphics: GraphicsSettings, event_loop: &EventLoopWindowTarget<T>, ui: Box<dyn UI>, created_at: Instant) -> Self {
+        let (window, gl_config) = create_window_and_gl_config(wb, graphics.multisampling, event_loop);
         let inner_window = window.expect("create winit window error");
-        let graphic = Graphic::new(inner_window.inner_size(), inner_window.raw_window_handle(), gl_config);
+        let graphic = Graphic::new(inner_window.inner_size(), inner_window.raw_window_handle(), gl_config, graphics.vsync);
+        let fullscreen = inner_window.fullscreen().is_some();
         Window {
             inner_window,
             graphic,
-            ui: Box::new(ui),
+            ui,
+            fullscreen,
+            pipeline: InputPipeline::default(),
+            pending_chord: Vec::new(),
+            shortcut_keys: Vec::new(),
+            file_drag: None,
+            created_at,
         }
     }
 
@@ -40,10 +91,46 @@ impl Window {
         self.graphic.on_resize(size)
     }
 
+    fn check_fullscreen_change(&mut self, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        let fullscreen = self.inner_window.fullscreen().is_some();
+        if fullscreen != self.fullscreen {
+            self.fullscreen = fullscreen;
+            if !fullscreen {
+                // Leaving fullscreen is asynchronous on macOS and X11, geometry set before this point
+                // would be overridden by the window manager.
+                if let Some(geometry) = application.take_fullscreen_geometry(self.id()) {
+                    geometry.restore(&self.inner_window);
+                }
+            }
+            self.ui.handle_event(UIEvent::FullscreenChange(fullscreen), &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+        }
+    }
+
     pub fn on_created(&mut self, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>){
         self.ui.handle_event(UIEvent::WindowCreate, &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
     }
 
+    pub fn on_close_requested(&mut self, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        let mut window_context = WindowContext::new(Context::new(application, event_loop), &mut self.inner_window);
+        self.ui.handle_event(UIEvent::CloseRequested, &mut window_context);
+        if !window_context.is_close_prevented() {
+            window_context.close_window();
+        }
+    }
+
+    pub fn on_modal_close(&mut self, modal_id: WindowId, result: Option<Box<dyn Any>>, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        self.ui.handle_event(UIEvent::ModalClose(modal_id, result), &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+    }
+
+    pub fn on_message(&mut self, message: WindowMessage, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        self.ui.handle_event(UIEvent::Message(message), &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+    }
+
+    #[cfg(feature = "gamepad")]
+    pub fn on_gamepad(&mut self, event: GamepadEvent, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        self.ui.handle_event(UIEvent::Gamepad(event), &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+    }
+
     pub fn on_timeout(&mut self, id: TimerId, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>){
         self.ui.handle_event(UIEvent::TimerOut(id), &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
     }
@@ -52,6 +139,24 @@ impl Window {
         self.ui.handle_event(UIEvent::Interval(id), &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
     }
 
+    pub fn on_animation_frame(&mut self, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        let timestamp = application.now();
+        let (finished, running) = application.tick_animations(self.id(), timestamp);
+        for animation in finished {
+            self.ui.handle_event(UIEvent::AnimationEnd(animation.id), &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+            if let Some(on_end) = animation.on_end {
+                on_end(&mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+            }
+        }
+        if running {
+            self.request_redraw();
+        }
+        let frames = application.take_animation_frames(self.id());
+        for (_, callback) in frames {
+            callback(&mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window), timestamp);
+        }
+    }
+
     pub fn draw(&mut self) {
         self.inner_window.pre_present_notify();
         self.graphic.draw(|canvas| {
@@ -65,18 +170,40 @@ impl Window {
     }
 
     pub fn handle_event(&mut self, event: WindowEvent, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) {
-        match event {
+        match &event {
             WindowEvent::ActivationTokenDone { .. } => {}
             WindowEvent::Resized(size) => {
-                self.on_resize(size);
+                self.on_resize(*size);
+                self.check_fullscreen_change(application, event_loop);
             }
             WindowEvent::Moved(_) => {}
-            WindowEvent::CloseRequested => {}
+            WindowEvent::CloseRequested => {
+                self.on_close_requested(application, event_loop);
+                return;
+            }
             WindowEvent::Destroyed => {}
-            WindowEvent::DroppedFile(_) => {}
-            WindowEvent::HoveredFile(_) => {}
-            WindowEvent::HoveredFileCancelled => {}
-            WindowEvent::Focused(_) => {}
+            WindowEvent::DroppedFile(path) => {
+                self.file_drag.get_or_insert_with(FileDrag::default).dropped.push(path.clone());
+                return;
+            }
+            WindowEvent::HoveredFile(path) => {
+                let drag = self.file_drag.get_or_insert_with(FileDrag::default);
+                drag.paths.push(path.clone());
+                drag.hovered = true;
+                return;
+            }
+            WindowEvent::HoveredFileCancelled => {
+                if self.file_drag.take().is_some_and(|drag| drag.entered) {
+                    self.ui.handle_event(UIEvent::FileDragLeave, &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+                }
+                return;
+            }
+            WindowEvent::Focused(false) => {
+                // Keys released while another window has the focus are never reported here.
+                self.pending_chord.clear();
+                self.shortcut_keys.clear();
+            }
+            WindowEvent::Focused(true) => {}
             WindowEvent::KeyboardInput { .. } => {}
             WindowEvent::ModifiersChanged(_) => {}
             WindowEvent::Ime(_) => {}
@@ -95,22 +222,259 @@ impl Window {
             WindowEvent::ThemeChanged(_) => {}
             WindowEvent::Occluded(_) => {}
             WindowEvent::RedrawRequested => {
+                self.on_animation_frame(application, event_loop);
                 self.draw();
             }
         }
-        self.ui.handle_event(UIEvent::Other(event), &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+        let scale_factor = self.inner_window.scale_factor();
+        if let Some(input) = InputEvent::from_window_event(&event, scale_factor) {
+            if !self.handle_input(input, application, event_loop) {
+                self.ui.handle_event(UIEvent::Other(event), &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+            }
+            return;
+        }
+        let ui_event = match event {
+            WindowEvent::Resized(size) => {
+                let size = size.to_logical::<f32>(scale_factor);
+                UIEvent::Resize(Size::new(size.width, size.height))
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => UIEvent::ScaleFactorChange(scale_factor),
+            WindowEvent::Focused(true) => UIEvent::FocusGained,
+            WindowEvent::Focused(false) => UIEvent::FocusLost,
+            event => UIEvent::Other(event),
+        };
+        self.ui.handle_event(ui_event, &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+    }
+
+    /// Runs input through shortcuts, pointer state and gesture recognition, then hands the resulting
+    /// `UIEvent`s to the UI. Returns `false` if nothing was delivered.
+    pub(crate) fn handle_input(&mut self, input: InputEvent, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) -> bool {
+        let window_id = self.inner_window.id();
+        let now = application.now();
+        application.record_input(window_id, now.saturating_duration_since(self.created_at), &input);
+        if let InputEvent::Key { key, physical_key, pressed, repeat, .. } = &input {
+            if self.handle_shortcut(key, *physical_key, *pressed, *repeat, application, event_loop) {
+                return true;
+            }
+        }
+        let captures = application.pointer_captures(window_id);
+        let ui_events = self.pipeline.process(&input, now, &captures);
+        if self.pipeline.gestures.take_long_press_request() {
+            application.add_timer(LONG_PRESS_TIME, Box::new(move |_, application, event_loop| {
+                if let Some(mut window) = application.remove_window(window_id) {
+                    window.on_long_press_timeout(application, event_loop);
+                    application.add_window(window_id, window);
+                }
+            }));
+        }
+        let delivered = !ui_events.is_empty();
+        for ui_event in ui_events {
+            let released = match &ui_event {
+                UIEvent::PointerUp(pointer) if pointer.buttons.is_empty() => Some(pointer.pointer_id),
+                UIEvent::PointerCancel(pointer) => Some(pointer.pointer_id),
+                _ => None,
+            };
+            self.ui.handle_event(ui_event, &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+            if let Some(pointer_id) = released {
+                application.release_pointer_capture(window_id, pointer_id);
+            }
+        }
+        if let InputEvent::CursorMoved { .. } = input {
+            if self.file_drag.as_ref().is_some_and(|drag| drag.entered) {
+                self.on_file_drag_over(application, event_loop);
+            }
+        }
+        delivered
+    }
+
+    /// Returns `true` if the key belongs to a shortcut and must not reach the UI as a key event.
+    fn handle_shortcut(&mut self, key: &Key, physical_key: PhysicalKey, pressed: bool, repeat: bool, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) -> bool {
+        let held = self.shortcut_keys.iter().position(|(held_key, held_physical_key, _)| match physical_key {
+            PhysicalKey::Code(_) => *held_physical_key == physical_key,
+            // Keys the platform can't identify have no physical key to tell them apart.
+            PhysicalKey::Unidentified(_) => held_key == key,
+        });
+        if !pressed {
+            return match held {
+                Some(index) => {
+                    self.shortcut_keys.remove(index);
+                    true
+                }
+                None => false,
+            };
+        }
+        if repeat {
+            let Some(index) = held else {
+                return false;
+            };
+            if let Some(command) = self.shortcut_keys[index].2.clone() {
+                self.ui.handle_event(UIEvent::Command(command), &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+            }
+            return true;
+        }
+        if let Key::Named(NamedKey::Control | NamedKey::Shift | NamedKey::Alt | NamedKey::Super | NamedKey::Meta) = key {
+            return false;
+        }
+        let chord_started = !self.pending_chord.is_empty();
+        self.pending_chord.push((key.clone(), physical_key, self.pipeline.input.modifiers()));
+        let (used, repeated_command) = match application.lookup_shortcut(self.inner_window.id(), &self.pending_chord) {
+            ShortcutMatch::Command { command, repeatable } => {
+                self.pending_chord.clear();
+                self.ui.handle_event(UIEvent::Command(command.clone()), &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+                (true, Some(command).filter(|_| repeatable))
+            }
+            ShortcutMatch::Pending => (true, None),
+            ShortcutMatch::None => {
+                // A key that doesn't continue the chord cancels it and is swallowed, like in most editors.
+                self.pending_chord.clear();
+                (chord_started, None)
+            }
+        };
+        if used {
+            if let Some(index) = held {
+                self.shortcut_keys.remove(index);
+            }
+            self.shortcut_keys.push((key.clone(), physical_key, repeated_command));
+        }
+        used
+    }
+
+    pub(crate) fn created_at(&self) -> Instant {
+        self.created_at
+    }
+
+    pub(crate) fn has_pending_file_drag(&self) -> bool {
+        self.file_drag.as_ref().is_some_and(|drag| !drag.entered || drag.hovered || !drag.dropped.is_empty())
+    }
+
+    /// Delivers the files reported since the last event loop iteration.
+    pub(crate) fn flush_file_drag(&mut self, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        let Some(drag) = &mut self.file_drag else {
+            return;
+        };
+        let position = self.pipeline.input.cursor_position();
+        if !drag.entered {
+            drag.entered = true;
+            let event = FileDragEvent {
+                paths: drag.paths.clone(),
+                position,
+            };
+            let mut window_context = WindowContext::new(Context::new(application, event_loop), &mut self.inner_window);
+            self.ui.handle_event(UIEvent::FileDragEnter(event), &mut window_context);
+            drag.accepted = window_context.is_drop_accepted();
+        }
+        if drag.hovered && drag.dropped.is_empty() {
+            drag.hovered = false;
+            self.on_file_drag_over(application, event_loop);
+        }
+        let Some(drag) = &mut self.file_drag else {
+            return;
+        };
+        if drag.dropped.is_empty() {
+            return;
+        }
+        let Some(drag) = self.file_drag.take() else {
+            return;
+        };
+        let event = match drag.accepted {
+            true => UIEvent::FileDrop(FileDragEvent {
+                paths: drag.dropped,
+                position,
+            }),
+            false => UIEvent::FileDragLeave,
+        };
+        self.ui.handle_event(event, &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+    }
+
+    fn on_file_drag_over(&mut self, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        let Some(drag) = &mut self.file_drag else {
+            return;
+        };
+        let event = FileDragEvent {
+            paths: drag.paths.clone(),
+            position: self.pipeline.input.cursor_position(),
+        };
+        let mut window_context = WindowContext::new(Context::new(application, event_loop), &mut self.inner_window);
+        self.ui.handle_event(UIEvent::FileDragOver(event), &mut window_context);
+        drag.accepted = window_context.is_drop_accepted();
+    }
+
+    fn on_long_press_timeout(&mut self, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        if let Some(gesture) = self.pipeline.gestures.check_long_press(application.now()) {
+            self.ui.handle_event(UIEvent::Gesture(gesture), &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+        }
+    }
+
+    pub(crate) fn on_close(&mut self, application: &mut Application, event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        self.ui.handle_event(UIEvent::WindowClose, &mut WindowContext::new(Context::new(application, event_loop), &mut self.inner_window));
+    }
+}
+
+impl WindowControl for Window {
+    fn winit_window(&self) -> &WinitWindow {
+        &self.inner_window
+    }
+}
+
+/// Makes windows built from `wb` owned by `owner` where the platform supports it,
+/// keeping them above it and hiding them with it. Only Windows has owned windows, elsewhere `wb` is
+/// returned unchanged and the window manager stacks the window like any other.
+#[cfg(target_os = "windows")]
+pub(crate) fn owned_window_builder(owner: &WinitWindow, wb: WindowBuilder) -> WindowBuilder {
+    use raw_window_handle::RawWindowHandle;
+    use winit::platform::windows::WindowBuilderExtWindows;
+    match owner.raw_window_handle() {
+        RawWindowHandle::Win32(handle) => wb.with_owner_window(handle.hwnd as _),
+        _ => wb,
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn owned_window_builder(_owner: &WinitWindow, wb: WindowBuilder) -> WindowBuilder {
+    wb
+}
+
+/// Places undecorated popup windows built from `wb` at `position`, relative to the top-left corner of `parent`'s client area.
+pub(crate) fn popup_window_builder(parent: &WinitWindow, position: Position, wb: WindowBuilder) -> WindowBuilder {
+    let wb = popup_platform_attributes(owned_window_builder(parent, wb))
+        .with_decorations(false)
+        .with_active(false);
+    match parent.inner_position() {
+        Ok(origin) => {
+            let offset: PhysicalPosition<i32> = position.to_physical(parent.scale_factor());
+            wb.with_position(PhysicalPosition::new(origin.x + offset.x, origin.y + offset.y))
+        }
+        Err(_) => wb,
     }
+}
 
-    pub(crate) fn on_close(&self) {}
+#[cfg(target_os = "windows")]
+fn popup_platform_attributes(wb: WindowBuilder) -> WindowBuilder {
+    use winit::platform::windows::WindowBuilderExtWindows;
+    wb.with_skip_taskbar(true)
 }
 
-fn create_window_and_gl_config<T: 'static>(wb: WindowBuilder, event_loop: &EventLoopWindowTarget<T>) -> (Option<WinitWindow>, GLConfig) {
-    let template = ConfigTemplateBuilder::new()
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "android"))))]
+fn popup_platform_attributes(wb: WindowBuilder) -> WindowBuilder {
+    use winit::platform::x11::{WindowBuilderExtX11, XWindowType};
+    wb.with_x11_window_type(vec![XWindowType::PopupMenu])
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "android"))))))]
+fn popup_platform_attributes(wb: WindowBuilder) -> WindowBuilder {
+    wb
+}
+
+fn create_window_and_gl_config<T: 'static>(wb: WindowBuilder, multisampling: u8, event_loop: &EventLoopWindowTarget<T>) -> (Option<WinitWindow>, GLConfig) {
+    let mut template = ConfigTemplateBuilder::new()
         .with_alpha_size(8)
         .with_transparency(true);
+    if multisampling > 0 {
+        template = template.with_multisampling(multisampling);
+    }
     let display_builder = DisplayBuilder::new().with_window_builder(Some(wb));
     display_builder
-        .build(&event_loop, template, |configs| {
+        .build(event_loop, template, |configs| {
             configs
                 .reduce(|accum, config| {
                     let transparency_check = config.supports_transparency().unwrap_or(false)
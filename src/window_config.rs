@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use winit::dpi::{LogicalPosition, LogicalSize};
+use winit::window::WindowBuilder;
+use crate::config_file::{self, ConfigFileError};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    /// MSAA sample count, `0` disables multisampling.
+    pub multisampling: u8,
+    pub vsync: bool,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            multisampling: 0,
+            vsync: true,
+        }
+    }
+}
+
+/// One window of a `WindowConfigFile`. Sizes and positions are in logical pixels, every field can be
+/// left out for its default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    /// Name of the UI factory registered with `EventHandler::register_ui`.
+    pub ui: String,
+    /// Opens the window with `Context::new_named_window` when set.
+    pub name: Option<String>,
+    pub title: String,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub resizable: bool,
+    pub decorations: bool,
+    pub transparent: bool,
+    pub maximized: bool,
+    pub visible: bool,
+    pub graphics: GraphicsSettings,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            ui: String::new(),
+            name: None,
+            title: String::new(),
+            width: None,
+            height: None,
+            x: None,
+            y: None,
+            resizable: true,
+            decorations: true,
+            transparent: false,
+            maximized: false,
+            visible: true,
+            graphics: GraphicsSettings::default(),
+        }
+    }
+}
+
+impl WindowConfig {
+    pub fn window_builder(&self) -> WindowBuilder {
+        let mut wb = WindowBuilder::new()
+            .with_title(&self.title)
+            .with_resizable(self.resizable)
+            .with_decorations(self.decorations)
+            .with_transparent(self.transparent)
+            .with_maximized(self.maximized)
+            .with_visible(self.visible);
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            wb = wb.with_inner_size(LogicalSize::new(width, height));
+        }
+        if let (Some(x), Some(y)) = (self.x, self.y) {
+            wb = wb.with_position(LogicalPosition::new(x, y));
+        }
+        wb
+    }
+}
+
+/// Initial windows of an application, opened after the init handler when set with `EventHandler::set_window_config`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WindowConfigFile {
+    #[serde(default)]
+    pub windows: Vec<WindowConfig>,
+}
+
+impl WindowConfigFile {
+    /// Reads a `.toml` or `.json` file, chosen by its extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigFileError> {
+        config_file::load(path)
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, ConfigFileError> {
+        config_file::from_toml(content)
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, ConfigFileError> {
+        config_file::from_json(content)
+    }
+
+    /// Every window with the factory registered under its UI name, or the error to report for it.
+    pub(crate) fn resolve<'a, F>(&'a self, factories: &'a HashMap<String, F>) -> Vec<Result<(&'a WindowConfig, &'a F), WindowConfigError>> {
+        self.windows.iter()
+            .map(|config| match factories.get(&config.ui) {
+                Some(factory) => Ok((config, factory)),
+                None => Err(WindowConfigError::UnknownUi {
+                    ui: config.ui.clone(),
+                    title: config.title.clone(),
+                }),
+            })
+            .collect()
+    }
+}
+
+/// A window of a `WindowConfigFile` that couldn't be opened, reported to `EventHandler::add_window_config_error_handler`.
+#[derive(Debug)]
+pub enum WindowConfigError {
+    /// A window names a UI that wasn't registered with `EventHandler::register_ui`, so it wasn't opened.
+    UnknownUi {
+        ui: String,
+        title: String,
+    },
+}
+
+impl Display for WindowConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowConfigError::UnknownUi { ui, title } => write!(f, "no UI registered as \"{}\", window \"{}\" not opened", ui, title),
+        }
+    }
+}
+
+impl Error for WindowConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use super::*;
+
+    const TOML: &str = r#"
+        [[windows]]
+        ui = "editor"
+        name = "main"
+        title = "Editor"
+        width = 800.0
+        height = 600.0
+        graphics = { multisampling = 4 }
+
+        [[windows]]
+        ui = "palette"
+        resizable = false
+    "#;
+
+    const JSON: &str = r#"{
+        "windows": [
+            { "ui": "editor", "name": "main", "title": "Editor", "width": 800.0, "height": 600.0, "graphics": { "multisampling": 4 } },
+            { "ui": "palette", "resizable": false }
+        ]
+    }"#;
+
+    fn expected() -> WindowConfigFile {
+        WindowConfigFile {
+            windows: vec![
+                WindowConfig {
+                    ui: "editor".into(),
+                    name: Some("main".into()),
+                    title: "Editor".into(),
+                    width: Some(800.0),
+                    height: Some(600.0),
+                    graphics: GraphicsSettings { multisampling: 4, vsync: true },
+                    ..WindowConfig::default()
+                },
+                WindowConfig {
+                    ui: "palette".into(),
+                    resizable: false,
+                    ..WindowConfig::default()
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn toml_and_json_read_the_same() {
+        assert_eq!(WindowConfigFile::from_toml(TOML).unwrap(), expected());
+        assert_eq!(WindowConfigFile::from_json(JSON).unwrap(), expected());
+    }
+
+    #[test]
+    fn missing_fields_take_their_defaults() {
+        let config = WindowConfigFile::from_toml("[[windows]]").unwrap();
+        assert_eq!(config.windows, vec![WindowConfig::default()]);
+        let window = &config.windows[0];
+        assert!(window.title.is_empty() && window.resizable && window.decorations && window.visible);
+        assert_eq!(WindowConfigFile::from_json("{}").unwrap(), WindowConfigFile::default());
+    }
+
+    #[test]
+    fn wrong_types_are_errors() {
+        assert!(matches!(WindowConfigFile::from_toml("[[windows]]\nwidth = \"wide\""), Err(ConfigFileError::Toml(_))));
+        assert!(matches!(WindowConfigFile::from_json(r#"{"windows": [{"resizable": 1}]}"#), Err(ConfigFileError::Json(_))));
+    }
+
+    #[test]
+    fn unknown_extension_is_an_error() {
+        let path = std::env::temp_dir().join(format!("skia-desktop-windows-{}.yaml", std::process::id()));
+        fs::write(&path, TOML).unwrap();
+        assert!(matches!(WindowConfigFile::load(&path), Err(ConfigFileError::UnknownFormat)));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn unknown_factory_name_is_reported() {
+        let factories = HashMap::from([("editor".to_string(), ())]);
+        let config = expected();
+        let resolved = config.resolve(&factories);
+        assert!(matches!(resolved[0], Ok((config, _)) if config.ui == "editor"));
+        match &resolved[1] {
+            Err(WindowConfigError::UnknownUi { ui, .. }) => assert_eq!(ui, "palette"),
+            _ => panic!("palette isn't registered"),
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use winit::dpi::{PhysicalPosition, PhysicalSize, Position, Size};
+use winit::error::ExternalError;
+use winit::monitor::MonitorHandle;
+use winit::window::{CursorGrabMode, CursorIcon, Fullscreen, Icon, ImePurpose, ResizeDirection, Theme, UserAttentionType, WindowButtons, WindowLevel};
+use winit::window::Window as WinitWindow;
+
+/// Window controls shared by `Window` and `WindowContext`.
+/// Operations a platform doesn't support are ignored or reported through the return value, never by panicking.
+pub trait WindowControl {
+    fn winit_window(&self) -> &WinitWindow;
+
+    fn title(&self) -> String {
+        self.winit_window().title()
+    }
+
+    fn set_title(&self, title: &str) {
+        self.winit_window().set_title(title);
+    }
+
+    fn inner_size(&self) -> PhysicalSize<u32> {
+        self.winit_window().inner_size()
+    }
+
+    fn outer_size(&self) -> PhysicalSize<u32> {
+        self.winit_window().outer_size()
+    }
+
+    /// Returns the new size if it was applied immediately, otherwise a `Resized` event follows.
+    fn set_inner_size(&self, size: impl Into<Size>) -> Option<PhysicalSize<u32>> where Self: Sized {
+        self.winit_window().request_inner_size(size)
+    }
+
+    fn set_min_inner_size(&self, size: Option<impl Into<Size>>) where Self: Sized {
+        self.winit_window().set_min_inner_size(size);
+    }
+
+    fn set_max_inner_size(&self, size: Option<impl Into<Size>>) where Self: Sized {
+        self.winit_window().set_max_inner_size(size);
+    }
+
+    fn inner_position(&self) -> Option<PhysicalPosition<i32>> {
+        self.winit_window().inner_position().ok()
+    }
+
+    fn outer_position(&self) -> Option<PhysicalPosition<i32>> {
+        self.winit_window().outer_position().ok()
+    }
+
+    fn set_outer_position(&self, position: impl Into<Position>) where Self: Sized {
+        self.winit_window().set_outer_position(position);
+    }
+
+    fn scale_factor(&self) -> f64 {
+        self.winit_window().scale_factor()
+    }
+
+    fn is_resizable(&self) -> bool {
+        self.winit_window().is_resizable()
+    }
+
+    fn set_resizable(&self, resizable: bool) {
+        self.winit_window().set_resizable(resizable);
+    }
+
+    fn set_enabled_buttons(&self, buttons: WindowButtons) {
+        self.winit_window().set_enabled_buttons(buttons);
+    }
+
+    fn is_decorated(&self) -> bool {
+        self.winit_window().is_decorated()
+    }
+
+    fn set_decorations(&self, decorations: bool) {
+        self.winit_window().set_decorations(decorations);
+    }
+
+    fn set_transparent(&self, transparent: bool) {
+        self.winit_window().set_transparent(transparent);
+    }
+
+    fn set_window_level(&self, level: WindowLevel) {
+        self.winit_window().set_window_level(level);
+    }
+
+    fn set_always_on_top(&self, always_on_top: bool) {
+        let level = if always_on_top { WindowLevel::AlwaysOnTop } else { WindowLevel::Normal };
+        self.set_window_level(level);
+    }
+
+    /// `None` when the platform can't tell.
+    fn is_minimized(&self) -> Option<bool> {
+        self.winit_window().is_minimized()
+    }
+
+    fn set_minimized(&self, minimized: bool) {
+        self.winit_window().set_minimized(minimized);
+    }
+
+    fn is_maximized(&self) -> bool {
+        self.winit_window().is_maximized()
+    }
+
+    fn set_maximized(&self, maximized: bool) {
+        self.winit_window().set_maximized(maximized);
+    }
+
+    fn fullscreen(&self) -> Option<Fullscreen> {
+        self.winit_window().fullscreen()
+    }
+
+    fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        self.winit_window().set_fullscreen(fullscreen);
+    }
+
+    /// `None` when the platform can't tell.
+    fn is_visible(&self) -> Option<bool> {
+        self.winit_window().is_visible()
+    }
+
+    fn set_visible(&self, visible: bool) {
+        self.winit_window().set_visible(visible);
+    }
+
+    fn focus_window(&self) {
+        self.winit_window().focus_window();
+    }
+
+    fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
+        self.winit_window().request_user_attention(request_type);
+    }
+
+    fn theme(&self) -> Option<Theme> {
+        self.winit_window().theme()
+    }
+
+    fn set_theme(&self, theme: Option<Theme>) {
+        self.winit_window().set_theme(theme);
+    }
+
+    fn set_window_icon(&self, icon: Option<Icon>) {
+        self.winit_window().set_window_icon(icon);
+    }
+
+    fn set_cursor_icon(&self, cursor: CursorIcon) {
+        self.winit_window().set_cursor_icon(cursor);
+    }
+
+    fn set_cursor_visible(&self, visible: bool) {
+        self.winit_window().set_cursor_visible(visible);
+    }
+
+    fn set_cursor_position(&self, position: impl Into<Position>) -> Result<(), ExternalError> where Self: Sized {
+        self.winit_window().set_cursor_position(position)
+    }
+
+    fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        self.winit_window().set_cursor_grab(mode)
+    }
+
+    /// Lets an input method compose text for this window, enable it while a text field has the focus.
+    /// Composition is reported with `UIEvent::ImePreedit` and `UIEvent::ImeCommit`.
+    fn set_ime_allowed(&self, allowed: bool) {
+        self.winit_window().set_ime_allowed(allowed);
+    }
+
+    /// Area of the caret or of the text being composed, the input method places its candidate window next to it.
+    fn set_ime_cursor_area(&self, position: impl Into<Position>, size: impl Into<Size>) where Self: Sized {
+        self.winit_window().set_ime_cursor_area(position, size);
+    }
+
+    fn set_ime_purpose(&self, purpose: ImePurpose) {
+        self.winit_window().set_ime_purpose(purpose);
+    }
+
+    /// Moves the window with the cursor, call it while the left mouse button is pressed.
+    fn drag_window(&self) -> Result<(), ExternalError> {
+        self.winit_window().drag_window()
+    }
+
+    /// Resizes the window with the cursor, call it while the left mouse button is pressed.
+    fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
+        self.winit_window().drag_resize_window(direction)
+    }
+
+    fn current_monitor(&self) -> Option<MonitorHandle> {
+        self.winit_window().current_monitor()
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+pub(crate) mod tests {
+    use winit::event_loop::EventLoopWindowTarget;
+    use winit::window::WindowBuilder;
+    use crate::custom_event::CustomEvent;
+    use super::*;
+
+    struct Control(WinitWindow);
+
+    impl WindowControl for Control {
+        fn winit_window(&self) -> &WinitWindow {
+            &self.0
+        }
+    }
+
+    /// Run by the display test in `application`, winit allows a single event loop per process.
+    pub(crate) fn controls_apply_to_the_window(event_loop: &EventLoopWindowTarget<CustomEvent>) {
+        let window = Control(WindowBuilder::new().with_visible(false).build(event_loop).unwrap());
+        assert_eq!(window.is_visible(), Some(false));
+
+        window.set_resizable(false);
+        assert!(!window.is_resizable());
+        window.set_decorations(false);
+        assert!(!window.is_decorated());
+        window.set_always_on_top(true);
+
+        // Not supported everywhere, but never a panic.
+        window.set_enabled_buttons(WindowButtons::CLOSE);
+        window.set_theme(Some(Theme::Dark));
+        window.set_transparent(true);
+        window.request_user_attention(Some(UserAttentionType::Informational));
+        let _ = window.set_cursor_grab(CursorGrabMode::Confined);
+        let _ = window.drag_window();
+        let _ = window.drag_resize_window(ResizeDirection::East);
+        let _ = window.current_monitor();
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event_loop::EventLoopWindowTarget;
+use winit::monitor::MonitorHandle;
+use winit::window::{Fullscreen, WindowBuilder};
+use winit::window::Window as WinitWindow;
+use crate::work_area::{work_area, Area};
+
+/// The position and size are the window's outer frame, decorations included, as
+/// `WindowBuilder::with_position` takes the position.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowState {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    /// How much wider and taller the frame is than the client area, to restore it with `with_inner_size`.
+    #[serde(default)]
+    pub frame_width: u32,
+    #[serde(default)]
+    pub frame_height: u32,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    pub monitor: Option<String>,
+}
+
+/// Remembers the geometry of named windows across runs.
+/// Install it with `Context::set_window_state_manager` and open windows with `Context::new_named_window`.
+pub struct WindowStateManager {
+    path: PathBuf,
+    states: HashMap<String, WindowState>,
+    dirty: bool,
+}
+
+impl WindowStateManager {
+    /// Stores the state in `window-state.json` under the user config directory, in a folder named `app_id`.
+    pub fn new(app_id: &str) -> Self {
+        let path = dirs::config_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join(app_id)
+            .join("window-state.json");
+        Self::with_path(path)
+    }
+
+    /// A missing or unreadable file starts with no saved state.
+    pub fn with_path(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let states = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            states,
+            dirty: false,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, name: &str) -> Option<&WindowState> {
+        self.states.get(name)
+    }
+
+    /// Captures the current state of the window. While it is maximized or fullscreen the previous
+    /// position and size are kept, so restoring it un-maximized brings back the normal geometry.
+    pub fn record(&mut self, name: &str, window: &WinitWindow) {
+        let maximized = window.is_maximized();
+        let fullscreen = window.fullscreen().is_some();
+        let monitor = window.current_monitor().and_then(|monitor| monitor.name());
+        let mut state = self.states.get(name).cloned().unwrap_or(WindowState {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+            frame_width: 0,
+            frame_height: 0,
+            maximized,
+            fullscreen,
+            monitor: None,
+        });
+        if !maximized && !fullscreen && window.is_minimized() != Some(true) {
+            if let Ok(position) = window.outer_position() {
+                state.x = position.x;
+                state.y = position.y;
+            }
+            let (outer, inner) = (window.outer_size(), window.inner_size());
+            state.width = outer.width;
+            state.height = outer.height;
+            state.frame_width = outer.width.saturating_sub(inner.width);
+            state.frame_height = outer.height.saturating_sub(inner.height);
+        }
+        state.maximized = maximized;
+        state.fullscreen = fullscreen;
+        state.monitor = monitor;
+        if self.states.get(name) != Some(&state) {
+            self.states.insert(name.to_string(), state);
+            self.dirty = true;
+        }
+    }
+
+    pub fn forget(&mut self, name: &str) {
+        if self.states.remove(name).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    /// Writes the file if anything changed since the last save.
+    pub fn save(&mut self) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec_pretty(&self.states)?;
+        fs::write(&self.path, data)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Applies the saved state of `name` to `wb`, moved and shrunk as needed to fit in the work area of
+    /// a connected monitor.
+    pub fn restore<T>(&self, name: &str, wb: WindowBuilder, event_loop: &EventLoopWindowTarget<T>) -> WindowBuilder {
+        let Some(state) = self.states.get(name) else {
+            return wb;
+        };
+        let primary = event_loop.primary_monitor();
+        let mut monitors: Vec<MonitorHandle> = event_loop.available_monitors().collect();
+        // The primary monitor first, the fallback for windows saved on a monitor that is gone.
+        monitors.sort_by_key(|monitor| Some(monitor) != primary.as_ref());
+        let screens: Vec<Screen> = monitors.iter()
+            .map(|monitor| Screen {
+                name: monitor.name(),
+                bounds: Area::bounds(monitor),
+                work_area: work_area(monitor),
+            })
+            .collect();
+        let Some(index) = choose_screen(state, &screens) else {
+            return wb;
+        };
+
+        let mut wb = wb.with_maximized(state.maximized);
+        if state.width > 0 && state.height > 0 {
+            let (position, size) = screens[index].work_area.fit(PhysicalPosition::new(state.x, state.y), PhysicalSize::new(state.width, state.height));
+            let inner_size = PhysicalSize::new(
+                size.width.saturating_sub(state.frame_width).max(1),
+                size.height.saturating_sub(state.frame_height).max(1),
+            );
+            wb = wb.with_inner_size(inner_size).with_position(position);
+        }
+        if state.fullscreen {
+            wb = wb.with_fullscreen(Some(Fullscreen::Borderless(Some(monitors[index].clone()))));
+        }
+        wb
+    }
+}
+
+/// A connected monitor as seen when restoring.
+struct Screen {
+    name: Option<String>,
+    bounds: Area,
+    work_area: Area,
+}
+
+/// The monitor the window was on, found by its position and then by its name, otherwise the first one,
+/// which is the primary monitor.
+fn choose_screen(state: &WindowState, screens: &[Screen]) -> Option<usize> {
+    screens.iter().position(|screen| screen.bounds.contains(state.x, state.y))
+        .or_else(|| screens.iter().position(|screen| state.monitor.is_some() && screen.name == state.monitor))
+        .or(if screens.is_empty() { None } else { Some(0) })
+}
+
+/// Geometry to come back to when leaving fullscreen.
+pub(crate) struct WindowGeometry {
+    position: Option<PhysicalPosition<i32>>,
+    size: PhysicalSize<u32>,
+    maximized: bool,
+}
+
+impl WindowGeometry {
+    pub fn capture(window: &WinitWindow) -> Self {
+        Self {
+            position: window.outer_position().ok(),
+            size: window.inner_size(),
+            maximized: window.is_maximized(),
+        }
+    }
+
+    pub fn restore(&self, window: &WinitWindow) {
+        if self.maximized {
+            window.set_maximized(true);
+            return;
+        }
+        let _ = window.request_inner_size(self.size);
+        if let Some(position) = self.position {
+            window.set_outer_position(position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(x: i32, y: i32, width: u32, height: u32, monitor: Option<&str>) -> WindowState {
+        WindowState {
+            x,
+            y,
+            width,
+            height,
+            frame_width: 0,
+            frame_height: 0,
+            maximized: false,
+            fullscreen: false,
+            monitor: monitor.map(str::to_string),
+        }
+    }
+
+    fn screens() -> Vec<Screen> {
+        vec![
+            Screen {
+                name: Some("primary".into()),
+                bounds: Area::new(0, 0, 1920, 1080),
+                work_area: Area::new(0, 0, 1920, 1040),
+            },
+            Screen {
+                name: Some("right".into()),
+                bounds: Area::new(1920, 0, 2560, 1440),
+                work_area: Area::new(1920, 32, 2560, 1408),
+            },
+        ]
+    }
+
+    fn restored(state: &WindowState, screens: &[Screen]) -> (usize, PhysicalPosition<i32>, PhysicalSize<u32>) {
+        let index = choose_screen(state, screens).unwrap();
+        let (position, size) = screens[index].work_area.fit(PhysicalPosition::new(state.x, state.y), PhysicalSize::new(state.width, state.height));
+        (index, position, size)
+    }
+
+    #[test]
+    fn stays_where_it_was() {
+        let (index, position, size) = restored(&state(2000, 100, 800, 600, Some("right")), &screens());
+        assert_eq!((index, position, size), (1, PhysicalPosition::new(2000, 100), PhysicalSize::new(800, 600)));
+    }
+
+    #[test]
+    fn keeps_clear_of_the_taskbar() {
+        // Saved over the primary monitor's taskbar, the bottom 40 pixels.
+        let (_, position, size) = restored(&state(100, 700, 800, 600, None), &screens());
+        assert_eq!((position, size), (PhysicalPosition::new(100, 440), PhysicalSize::new(800, 600)));
+        // Too tall for the work area.
+        let (_, position, size) = restored(&state(0, 0, 1920, 1080, None), &screens());
+        assert_eq!((position, size), (PhysicalPosition::new(0, 0), PhysicalSize::new(1920, 1040)));
+    }
+
+    #[test]
+    fn moves_to_the_same_monitor_found_by_name() {
+        // The monitor on the right now sits elsewhere on the desktop.
+        let mut screens = screens();
+        screens[1].bounds = Area::new(-2560, 0, 2560, 1440);
+        screens[1].work_area = Area::new(-2560, 0, 2560, 1440);
+        let (index, position, _) = restored(&state(2000, 100, 800, 600, Some("right")), &screens);
+        assert_eq!((index, position), (1, PhysicalPosition::new(-800, 100)));
+    }
+
+    #[test]
+    fn recovers_off_screen_windows_on_the_primary_monitor() {
+        let (index, position, size) = restored(&state(5000, 3000, 800, 600, Some("unplugged")), &screens());
+        assert_eq!((index, position, size), (0, PhysicalPosition::new(1120, 440), PhysicalSize::new(800, 600)));
+        assert_eq!(choose_screen(&state(5000, 3000, 800, 600, None), &[]), None);
+    }
+
+    #[test]
+    fn old_files_without_frame_sizes_still_load() {
+        let json = r#"{"x":1,"y":2,"width":3,"height":4,"maximized":false,"fullscreen":true,"monitor":null}"#;
+        let state: WindowState = serde_json::from_str(json).unwrap();
+        assert_eq!((state.frame_width, state.frame_height, state.fullscreen), (0, 0, true));
+    }
+}
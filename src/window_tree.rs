@@ -0,0 +1,192 @@
+use std::any::Any;
+use std::collections::HashMap;
+use winit::window::WindowId;
+
+/// Parent, modal and popup relations between the open windows, apart from the windows themselves.
+#[derive(Default)]
+pub(crate) struct WindowTree {
+    parents: HashMap<WindowId, WindowId>,
+    /// Owner to the modal window open on it.
+    modals: HashMap<WindowId, WindowId>,
+    modal_results: HashMap<WindowId, Box<dyn Any>>,
+    popups: Vec<WindowId>,
+}
+
+impl WindowTree {
+    pub fn set_parent(&mut self, window_id: WindowId, parent_id: WindowId) {
+        self.parents.insert(window_id, parent_id);
+    }
+
+    pub fn parent(&self, window_id: WindowId) -> Option<WindowId> {
+        self.parents.get(&window_id).copied()
+    }
+
+    pub fn children(&self, window_id: WindowId) -> Vec<WindowId> {
+        let mut children: Vec<WindowId> = self.parents.iter()
+            .filter(|(_, parent_id)| **parent_id == window_id)
+            .map(|(id, _)| *id)
+            .collect();
+        children.sort();
+        children
+    }
+
+    /// The window followed by its parent, the parent's parent and so on.
+    pub fn ancestors(&self, window_id: WindowId) -> Vec<WindowId> {
+        let mut ancestors = vec![window_id];
+        while let Some(parent_id) = self.parent(*ancestors.last().unwrap()) {
+            if ancestors.contains(&parent_id) {
+                break;
+            }
+            ancestors.push(parent_id);
+        }
+        ancestors
+    }
+
+    pub fn set_modal(&mut self, owner_id: WindowId, window_id: WindowId) {
+        self.modals.insert(owner_id, window_id);
+    }
+
+    pub fn modal(&self, owner_id: WindowId) -> Option<WindowId> {
+        self.modals.get(&owner_id).copied()
+    }
+
+    /// The innermost modal window open on `window_id`, the one that has to be closed first.
+    pub fn blocking_modal(&self, window_id: WindowId) -> Option<WindowId> {
+        let mut modal_id = self.modal(window_id)?;
+        while let Some(id) = self.modal(modal_id) {
+            modal_id = id;
+        }
+        Some(modal_id)
+    }
+
+    pub fn set_modal_result(&mut self, window_id: WindowId, result: Box<dyn Any>) {
+        self.modal_results.insert(window_id, result);
+    }
+
+    pub fn add_popup(&mut self, window_id: WindowId) {
+        self.popups.push(window_id);
+    }
+
+    /// Open popups, the most recent one last.
+    pub fn popups(&self) -> &[WindowId] {
+        &self.popups
+    }
+
+    /// Popups that aren't `window_id` or one of its ancestors.
+    pub fn popups_outside(&self, window_id: WindowId) -> Vec<WindowId> {
+        let ancestors = self.ancestors(window_id);
+        self.popups.iter().copied().filter(|popup_id| !ancestors.contains(popup_id)).collect()
+    }
+
+    /// The windows closing with `window_id`: its descendants, each before its parent, and the window itself last.
+    pub fn close_order(&self, window_id: WindowId) -> Vec<WindowId> {
+        let mut order = vec![];
+        self.push_close_order(window_id, &mut order);
+        order
+    }
+
+    fn push_close_order(&self, window_id: WindowId, order: &mut Vec<WindowId>) {
+        if order.contains(&window_id) {
+            return;
+        }
+        for child_id in self.children(window_id) {
+            self.push_close_order(child_id, order);
+        }
+        order.push(window_id);
+    }
+
+    /// Forgets the closed window. If it was modal, returns its owner and the result it was closed with.
+    pub fn remove(&mut self, window_id: WindowId) -> Option<(WindowId, Option<Box<dyn Any>>)> {
+        self.popups.retain(|id| *id != window_id);
+        self.modals.remove(&window_id);
+        let result = self.modal_results.remove(&window_id);
+        let owner_id = self.parents.remove(&window_id)?;
+        if self.modal(owner_id) != Some(window_id) {
+            return None;
+        }
+        self.modals.remove(&owner_id);
+        Some((owner_id, result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(id: u64) -> WindowId {
+        WindowId::from(id)
+    }
+
+    #[test]
+    fn closes_descendants_before_their_parents() {
+        let mut tree = WindowTree::default();
+        tree.set_parent(window(2), window(1));
+        tree.set_parent(window(3), window(2));
+        tree.set_parent(window(4), window(1));
+        assert_eq!(tree.close_order(window(1)), vec![window(3), window(2), window(4), window(1)]);
+        assert_eq!(tree.close_order(window(2)), vec![window(3), window(2)]);
+        assert_eq!(tree.close_order(window(5)), vec![window(5)]);
+    }
+
+    #[test]
+    fn closed_modal_hands_its_result_to_the_owner() {
+        let mut tree = WindowTree::default();
+        tree.set_parent(window(2), window(1));
+        tree.set_modal(window(1), window(2));
+        tree.set_modal_result(window(2), Box::new("saved"));
+        let (owner_id, result) = tree.remove(window(2)).unwrap();
+        assert_eq!(owner_id, window(1));
+        assert_eq!(result.unwrap().downcast_ref::<&str>(), Some(&"saved"));
+        assert_eq!(tree.modal(window(1)), None);
+        assert_eq!(tree.parent(window(2)), None);
+    }
+
+    #[test]
+    fn modal_closed_without_a_result() {
+        let mut tree = WindowTree::default();
+        tree.set_parent(window(2), window(1));
+        tree.set_modal(window(1), window(2));
+        let (owner_id, result) = tree.remove(window(2)).unwrap();
+        assert_eq!(owner_id, window(1));
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn owned_window_is_not_modal() {
+        let mut tree = WindowTree::default();
+        tree.set_parent(window(2), window(1));
+        tree.set_modal_result(window(2), Box::new(1));
+        assert!(tree.remove(window(2)).is_none());
+        assert!(tree.children(window(1)).is_empty());
+    }
+
+    #[test]
+    fn innermost_modal_blocks() {
+        let mut tree = WindowTree::default();
+        tree.set_parent(window(2), window(1));
+        tree.set_modal(window(1), window(2));
+        tree.set_parent(window(3), window(2));
+        tree.set_modal(window(2), window(3));
+        assert_eq!(tree.blocking_modal(window(1)), Some(window(3)));
+        assert_eq!(tree.blocking_modal(window(2)), Some(window(3)));
+        assert_eq!(tree.blocking_modal(window(3)), None);
+        tree.remove(window(3));
+        assert_eq!(tree.blocking_modal(window(1)), Some(window(2)));
+    }
+
+    #[test]
+    fn popups_outside_the_clicked_window() {
+        let mut tree = WindowTree::default();
+        tree.set_parent(window(2), window(1));
+        tree.add_popup(window(2));
+        tree.set_parent(window(3), window(2));
+        tree.add_popup(window(3));
+        assert_eq!(tree.ancestors(window(3)), vec![window(3), window(2), window(1)]);
+        // A click in the submenu keeps the menu open, a click in the main window closes both.
+        assert_eq!(tree.popups_outside(window(3)), vec![]);
+        assert_eq!(tree.popups_outside(window(2)), vec![window(3)]);
+        assert_eq!(tree.popups_outside(window(1)), vec![window(2), window(3)]);
+        tree.remove(window(3));
+        assert_eq!(tree.popups(), &[window(2)]);
+    }
+}
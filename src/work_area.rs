@@ -0,0 +1,152 @@
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::monitor::MonitorHandle;
+
+/// Rectangle on the virtual desktop, in physical pixels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Area {
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+}
+
+impl Area {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            position: PhysicalPosition::new(x, y),
+            size: PhysicalSize::new(width, height),
+        }
+    }
+
+    pub fn bounds(monitor: &MonitorHandle) -> Self {
+        Self {
+            position: monitor.position(),
+            size: monitor.size(),
+        }
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.position.x
+            && y >= self.position.y
+            && x < self.position.x + self.size.width as i32
+            && y < self.position.y + self.size.height as i32
+    }
+
+    pub fn intersection(&self, other: &Area) -> Option<Area> {
+        let left = self.position.x.max(other.position.x);
+        let top = self.position.y.max(other.position.y);
+        let right = (self.position.x + self.size.width as i32).min(other.position.x + other.size.width as i32);
+        let bottom = (self.position.y + self.size.height as i32).min(other.position.y + other.size.height as i32);
+        (right > left && bottom > top).then(|| Area::new(left, top, (right - left) as u32, (bottom - top) as u32))
+    }
+
+    /// Moves and shrinks a rectangle of `size` at `position` until it lies within this area.
+    pub fn fit(&self, position: PhysicalPosition<i32>, size: PhysicalSize<u32>) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        let size = PhysicalSize::new(size.width.min(self.size.width), size.height.min(self.size.height));
+        let x = position.x.clamp(self.position.x, self.position.x + (self.size.width - size.width) as i32);
+        let y = position.y.clamp(self.position.y, self.position.y + (self.size.height - size.height) as i32);
+        (PhysicalPosition::new(x, y), size)
+    }
+}
+
+/// The part of the monitor not covered by taskbars, docks or panels, the whole monitor where the
+/// platform can't tell, such as on Wayland.
+pub(crate) fn work_area(monitor: &MonitorHandle) -> Area {
+    let bounds = Area::bounds(monitor);
+    platform_work_area(monitor, &bounds)
+        .and_then(|work_area| work_area.intersection(&bounds))
+        .unwrap_or(bounds)
+}
+
+#[cfg(target_os = "windows")]
+fn platform_work_area(monitor: &MonitorHandle, _bounds: &Area) -> Option<Area> {
+    use winit::platform::windows::MonitorHandleExtWindows;
+    #[repr(C)]
+    struct Rect {
+        left: i32,
+        top: i32,
+        right: i32,
+        bottom: i32,
+    }
+    #[repr(C)]
+    struct MonitorInfo {
+        size: u32,
+        monitor: Rect,
+        work: Rect,
+        flags: u32,
+    }
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetMonitorInfoW(monitor: isize, info: *mut MonitorInfo) -> i32;
+    }
+    let mut info = MonitorInfo {
+        size: std::mem::size_of::<MonitorInfo>() as u32,
+        monitor: Rect { left: 0, top: 0, right: 0, bottom: 0 },
+        work: Rect { left: 0, top: 0, right: 0, bottom: 0 },
+        flags: 0,
+    };
+    if unsafe { GetMonitorInfoW(monitor.hmonitor(), &mut info) } == 0 {
+        return None;
+    }
+    let work = info.work;
+    Some(Area::new(work.left, work.top, (work.right - work.left).max(0) as u32, (work.bottom - work.top).max(0) as u32))
+}
+
+/// `visibleFrame` leaves out the menu bar and the Dock. AppKit measures in points from the bottom-left
+/// corner, so the insets of the visible frame are scaled and applied to the monitor's bounds.
+#[cfg(target_os = "macos")]
+fn platform_work_area(monitor: &MonitorHandle, bounds: &Area) -> Option<Area> {
+    use objc2_app_kit::NSScreen;
+    use winit::platform::macos::MonitorHandleExtMacOS;
+    let screen = monitor.ns_screen()? as *const NSScreen;
+    // winit hands out a retained `NSScreen`, alive while `monitor` is.
+    let screen = unsafe { &*screen };
+    let (frame, visible) = (screen.frame(), screen.visibleFrame());
+    let scale_factor = monitor.scale_factor();
+    let left = ((visible.origin.x - frame.origin.x) * scale_factor).round() as i32;
+    let top = ((frame.origin.y + frame.size.height - visible.origin.y - visible.size.height) * scale_factor).round() as i32;
+    let width = (visible.size.width * scale_factor).round() as u32;
+    let height = (visible.size.height * scale_factor).round() as u32;
+    Some(Area::new(bounds.position.x + left, bounds.position.y + top, width, height))
+}
+
+/// `_NET_WORKAREA` of the X11 root window, set by EWMH window managers for the whole desktop.
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "android"))))]
+fn platform_work_area(_monitor: &MonitorHandle, _bounds: &Area) -> Option<Area> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+    let (connection, screen) = x11rb::connect(None).ok()?;
+    let root = connection.setup().roots.get(screen)?.root;
+    let atom = connection.intern_atom(true, b"_NET_WORKAREA").ok()?.reply().ok()?.atom;
+    let property = connection.get_property(false, root, atom, AtomEnum::CARDINAL, 0, 4).ok()?.reply().ok()?;
+    let values: Vec<u32> = property.value32()?.collect();
+    let [x, y, width, height] = values[..] else {
+        return None;
+    };
+    Some(Area::new(x as i32, y as i32, width, height))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", all(unix, not(any(target_os = "ios", target_os = "android"))))))]
+fn platform_work_area(_monitor: &MonitorHandle, _bounds: &Area) -> Option<Area> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intersection() {
+        let desktop = Area::new(0, 0, 3840, 1040);
+        let right = Area::new(1920, 0, 1920, 1080);
+        assert_eq!(desktop.intersection(&right), Some(Area::new(1920, 0, 1920, 1040)));
+        assert_eq!(Area::new(0, 0, 10, 10).intersection(&Area::new(10, 0, 10, 10)), None);
+    }
+
+    #[test]
+    fn fit_moves_then_shrinks() {
+        let area = Area::new(100, 0, 1000, 800);
+        let (position, size) = area.fit(PhysicalPosition::new(900, -50), PhysicalSize::new(400, 300));
+        assert_eq!((position, size), (PhysicalPosition::new(700, 0), PhysicalSize::new(400, 300)));
+        let (position, size) = area.fit(PhysicalPosition::new(0, 0), PhysicalSize::new(1200, 900));
+        assert_eq!((position, size), (PhysicalPosition::new(100, 0), PhysicalSize::new(1000, 800)));
+    }
+}